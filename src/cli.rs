@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgMatches};
+
+use crate::services::config::Config;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Command {
+    // Waits until someone approves or rejects, or posts without buttons with `--mode notify`
+    Request,
+    // Appends status to a posted approval
+    Update,
+    // Validates the options without posting anything
+    Check,
+    // Serves a fake Slack for testing workflows offline
+    FakeSlack,
+}
+
+pub struct Cli {
+    pub command: Command,
+    pub config: Config,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Value,
+    // `--name` is `--name=true`
+    Flag,
+    // Repeated flags are joined into lines
+    List,
+}
+
+// Names are the same as the inputs of the action and the keys of the config file
+const REQUEST_OPTIONS: &[(&str, Kind, &str)] = &[
+    (
+        "platform",
+        Kind::Value,
+        "Chat platform to post to (slack, mattermost, teams)",
+    ),
+    (
+        "mode",
+        Kind::Value,
+        "`approval` to wait for approval, or `notify` to post without buttons and exit",
+    ),
+    ("bot-token", Kind::Value, "Slack bot token (xoxb-)"),
+    (
+        "app-token",
+        Kind::Value,
+        "Slack app token (xapp-). Not needed in notify mode",
+    ),
+    ("channel-id", Kind::List, "Slack channel IDs"),
+    (
+        "join-channel",
+        Kind::Flag,
+        "Join the public channels the bot is not in",
+    ),
+    ("mention-to-users", Kind::List, "Slack user IDs to mention"),
+    (
+        "mention-to-groups",
+        Kind::List,
+        "Slack group IDs to mention",
+    ),
+    (
+        "authorized-users",
+        Kind::List,
+        "Slack user IDs who can approve or reject",
+    ),
+    (
+        "authorized-groups",
+        Kind::List,
+        "Slack group IDs who can approve or reject",
+    ),
+    (
+        "supersede-pending",
+        Kind::Flag,
+        "Mark older pending approvals of the same workflow as superseded",
+    ),
+    (
+        "approval-timeout-minutes",
+        Kind::Value,
+        "Minutes to wait for approval",
+    ),
+    (
+        "thread-ts",
+        Kind::Value,
+        "Timestamp of the thread to post the approval into",
+    ),
+    (
+        "thread-match",
+        Kind::Value,
+        "Text of a recent message to post the approval into the thread of",
+    ),
+    (
+        "reply-broadcast",
+        Kind::Flag,
+        "Also post the approval in a thread to the channel",
+    ),
+    (
+        "post-to",
+        Kind::List,
+        "Where to post the approval (channel, direct-message)",
+    ),
+    ("title", Kind::Value, "Title of the message"),
+    (
+        "description",
+        Kind::Value,
+        "Description of the message in Slack mrkdwn",
+    ),
+    (
+        "fields",
+        Kind::List,
+        "Extra fields of the message. `Name: value` for each",
+    ),
+    (
+        "description-file",
+        Kind::Value,
+        "Path to a Markdown file shown as the description",
+    ),
+    (
+        "template-file",
+        Kind::Value,
+        "Path to a Block Kit template file in JSON or YAML",
+    ),
+    (
+        "commits-base",
+        Kind::Value,
+        "Ref or tag of the last deployment to list the commits since",
+    ),
+    (
+        "attachments",
+        Kind::List,
+        "Paths of files to upload into the thread",
+    ),
+    (
+        "terraform-plan",
+        Kind::Value,
+        "Path to the output of `terraform show -json`",
+    ),
+    (
+        "terraform-destroy",
+        Kind::Value,
+        "What to do when the plan destroys resources (allow, confirm, deny)",
+    ),
+    (
+        "ca-certificates",
+        Kind::List,
+        "Paths to PEM files of extra CA certificates",
+    ),
+    (
+        "slack-api-url",
+        Kind::Value,
+        "Base URL of the Slack Web API (default: https://slack.com/api)",
+    ),
+    (
+        "mattermost-url",
+        Kind::Value,
+        "URL of the Mattermost server",
+    ),
+    (
+        "mattermost-callback-url",
+        Kind::Value,
+        "URL where Mattermost sends the clicks, put into the buttons",
+    ),
+    (
+        "mattermost-listen-address",
+        Kind::Value,
+        "Address like 0.0.0.0:8080 where the clicks are served",
+    ),
+    (
+        "teams-app-id",
+        Kind::Value,
+        "Microsoft App ID of the Teams bot",
+    ),
+    (
+        "teams-tenant-id",
+        Kind::Value,
+        "Microsoft Entra tenant ID of the Teams bot",
+    ),
+    (
+        "teams-service-url",
+        Kind::Value,
+        "Bot Connector service URL (default: https://smba.trafficmanager.net/teams)",
+    ),
+    (
+        "teams-listen-address",
+        Kind::Value,
+        "Address like 127.0.0.1:3978 where the messaging endpoint of the Teams bot is served",
+    ),
+];
+
+const UPDATE_OPTIONS: &[(&str, Kind, &str)] = &[
+    ("bot-token", Kind::Value, "Slack bot token (xoxb-)"),
+    ("channel-id", Kind::Value, "Channel ID of the approval"),
+    ("message-ts", Kind::Value, "Timestamp of the approval"),
+    (
+        "thread-ts",
+        Kind::Value,
+        "Timestamp of the thread the approval is in",
+    ),
+    ("status", Kind::Value, "Status to append in Slack mrkdwn"),
+    (
+        "append-to",
+        Kind::Value,
+        "Where to append status (thread, message)",
+    ),
+    (
+        "ca-certificates",
+        Kind::List,
+        "Paths to PEM files of extra CA certificates",
+    ),
+    (
+        "slack-api-url",
+        Kind::Value,
+        "Base URL of the Slack Web API (default: https://slack.com/api)",
+    ),
+];
+
+const FAKE_SLACK_OPTIONS: &[(&str, Kind, &str)] = &[
+    (
+        "host",
+        Kind::Value,
+        "Address to listen on (default: 127.0.0.1)",
+    ),
+    ("port", Kind::Value, "Port to listen on (default: 3000)"),
+    (
+        "usergroups",
+        Kind::List,
+        "Group ID and its members like `S0123: U0123, U0456`",
+    ),
+];
+
+fn command() -> clap::Command {
+    clap::Command::new("slack-approval")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Ask for approval in Slack and wait until someone approves or rejects")
+        .after_help(
+            "Options are read from flags, `SLACK_APPROVAL_*` environment variables (e.g. `SLACK_APPROVAL_BOT_TOKEN`) and the config file in this order.",
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("PATH")
+                .global(true)
+                .help("Path to a YAML config file whose keys are the names of the options"),
+        )
+        .subcommand(with_options(
+            clap::Command::new("request")
+                .visible_alias("approve")
+                .about("Post an approval and wait until someone approves or rejects"),
+            REQUEST_OPTIONS,
+        ))
+        .subcommand(with_options(
+            clap::Command::new("update").about("Append status to a posted approval"),
+            UPDATE_OPTIONS,
+        ))
+        .subcommand(with_options(
+            clap::Command::new("check").about("Validate the options without posting anything"),
+            REQUEST_OPTIONS,
+        ))
+        .subcommand(with_options(
+            clap::Command::new("fake-slack")
+                .about("Serve a fake Slack to test workflows without a workspace"),
+            FAKE_SLACK_OPTIONS,
+        ))
+}
+
+fn with_options(
+    command: clap::Command,
+    options: &[(&'static str, Kind, &'static str)],
+) -> clap::Command {
+    command.args(options.iter().map(|(name, kind, help)| {
+        let arg = Arg::new(*name).long(*name).help(*help);
+        match kind {
+            Kind::Value => arg,
+            Kind::Flag => arg
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("true"),
+            Kind::List => arg.action(ArgAction::Append),
+        }
+    }))
+}
+
+// NOTE: The approval is requested without a subcommand for compatibility with the action
+pub fn parse() -> Result<Cli> {
+    parse_from(std::env::args())
+}
+
+fn parse_from<I, T>(args: I) -> Result<Cli>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let matches = command().get_matches_from(args);
+    let (command, mut flags) = match matches.subcommand() {
+        Some(("request", matches)) => (Command::Request, to_flags(matches)),
+        Some(("update", matches)) => (Command::Update, to_flags(matches)),
+        Some(("check", matches)) => (Command::Check, to_flags(matches)),
+        Some(("fake-slack", matches)) => (Command::FakeSlack, to_flags(matches)),
+        _ => (Command::Request, HashMap::new()),
+    };
+    if let Some(path) = matches.get_one::<String>("config") {
+        flags.insert("config".into(), path.clone());
+    }
+
+    Ok(Cli {
+        command,
+        config: Config::load(flags)?,
+    })
+}
+
+fn to_flags(matches: &ArgMatches) -> HashMap<String, String> {
+    matches
+        .ids()
+        .filter_map(|id| {
+            let values: Vec<&String> = matches.get_many::<String>(id.as_str())?.collect();
+            let value = values
+                .into_iter()
+                .map(String::as_str)
+                .collect::<Vec<&str>>()
+                .join("\n");
+            Some((id.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn should_verify_command() {
+        command().debug_assert();
+    }
+
+    #[rstest]
+    #[case(vec!["slack-approval"], Command::Request)]
+    #[case(vec!["slack-approval", "approve"], Command::Request)]
+    #[case(vec!["slack-approval", "request"], Command::Request)]
+    #[case(vec!["slack-approval", "update"], Command::Update)]
+    #[case(vec!["slack-approval", "check"], Command::Check)]
+    #[case(vec!["slack-approval", "fake-slack"], Command::FakeSlack)]
+    fn test_parse_command(#[case] args: Vec<&str>, #[case] expected: Command) {
+        assert_eq!(parse_from(args).unwrap().command, expected);
+    }
+
+    #[test]
+    fn should_read_flags() {
+        let matches = command().get_matches_from([
+            "slack-approval",
+            "request",
+            "--bot-token",
+            "xoxb-bot-token",
+            "--channel-id",
+            "C1234567890",
+            "--channel-id",
+            "C0987654321",
+            "--supersede-pending",
+            "--reply-broadcast=false",
+        ]);
+        let (_, matches) = matches.subcommand().unwrap();
+
+        assert_eq!(
+            to_flags(matches),
+            HashMap::from([
+                ("bot-token".into(), "xoxb-bot-token".into()),
+                ("channel-id".into(), "C1234567890\nC0987654321".into()),
+                ("supersede-pending".into(), "true".into()),
+                ("reply-broadcast".into(), "false".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_post_without_buttons_in_notify_mode() {
+        let cli = parse_from(["slack-approval", "request", "--mode", "notify"]).unwrap();
+
+        assert_eq!(
+            cli.config.get_optional("mode").unwrap(),
+            Some("notify".into())
+        );
+    }
+}
@@ -1,11 +1,20 @@
-use anyhow::Result;
-use tracing::error;
+use std::collections::HashMap;
 
+use anyhow::{Result, bail};
+use tracing::{error, info};
+
+use cli::Command;
+use services::github::github_inputs::Platform;
+
+mod cli;
 mod services;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt().init();
+    // NOTE: Logs go to stderr so that scripts can read the outputs from stdout
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
     if let Err(e) = execute().await {
         error!("Error occurred: {:?}", e);
         std::process::exit(1);
@@ -15,7 +24,70 @@ async fn main() -> Result<()> {
 }
 
 async fn execute() -> Result<()> {
-    let github_info = services::github::github_info::read_github_info()?;
-    let github_inputs = services::github::github_inputs::read_github_inputs()?;
-    services::slack::handle_slack_approval(&github_info, &github_inputs).await
+    // NOTE: The post step runs the same entrypoint, so the main step leaves a mark in the state
+    if services::github::file_commands::get_state(services::slack::STATE_IS_POST).is_some() {
+        let github_info = services::github::github_info::read_github_info();
+        let config = services::config::Config::load(HashMap::new())?;
+        let finalize_inputs = services::github::finalize_inputs::read_finalize_inputs(&config)?;
+        return services::slack::handle_slack_finalize(&github_info, &finalize_inputs).await;
+    }
+    let ci_info = services::ci::detect_ci_info();
+    // NOTE: Only the action has the post step, which runs after every command without arguments
+    if ci_info.github().is_some() {
+        services::github::file_commands::save_state(&[(
+            services::slack::STATE_IS_POST,
+            "true".into(),
+        )])?;
+    }
+
+    let cli = cli::parse()?;
+    match cli.command {
+        Command::Request => {
+            let github_inputs = services::github::github_inputs::read_github_inputs(&cli.config)?;
+            // Handle SIGINT and SIGTERM sent on cancellation to update the message before exiting
+            let termination = services::termination::Termination::install(github_inputs.timeout)?;
+            match github_inputs.platform {
+                Platform::Slack => {
+                    services::slack::handle_slack_approval(
+                        ci_info.as_ref(),
+                        &github_inputs,
+                        termination,
+                    )
+                    .await
+                }
+                Platform::Mattermost => {
+                    services::mattermost::handle_mattermost_approval(
+                        ci_info.as_ref(),
+                        &github_inputs,
+                        termination,
+                    )
+                    .await
+                }
+                Platform::Teams => {
+                    services::teams::handle_teams_approval(
+                        ci_info.as_ref(),
+                        &github_inputs,
+                        termination,
+                    )
+                    .await
+                }
+            }
+        }
+        Command::Update => {
+            let update_inputs = services::github::update_inputs::read_update_inputs(&cli.config)?;
+            services::slack::handle_slack_update(ci_info.as_ref(), &update_inputs).await
+        }
+        Command::Check => {
+            let github_inputs = services::github::github_inputs::read_github_inputs(&cli.config)?;
+            info!("Options are valid");
+            if github_inputs.platform != Platform::Slack {
+                bail!("`check` supports only Slack");
+            }
+            services::slack::handle_slack_check(&github_inputs).await
+        }
+        Command::FakeSlack => {
+            let options = services::fake_slack::read_fake_slack_options(&cli.config)?;
+            services::fake_slack::handle_fake_slack(&options).await
+        }
+    }
 }
@@ -0,0 +1,143 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use http::{Request, Response};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use super::Interaction;
+
+// How a platform reads the clicks out of the requests it sends
+pub trait Route: Send + Sync + 'static {
+    // Responds to the request, and sends the click when it is on this approval
+    fn route(
+        &self,
+        req: Request<Incoming>,
+        sender: &mpsc::UnboundedSender<Interaction>,
+    ) -> impl Future<Output = Response<Full<Bytes>>> + Send;
+}
+
+struct State<R> {
+    route: R,
+    sender: mpsc::UnboundedSender<Interaction>,
+}
+
+// Receives the clicks over HTTP while waiting for a decision
+// NOTE: The port is closed when this is dropped
+pub struct Callback {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+    receiver: mpsc::UnboundedReceiver<Interaction>,
+}
+
+impl Callback {
+    // NOTE: The URL the platform sends to may go through a proxy, so the address to listen on is given separately
+    pub async fn listen<R: Route>(listen_address: &str, route: R) -> Result<Self> {
+        let listener = TcpListener::bind(listen_address)
+            .await
+            .with_context(|| format!("Failed to listen on {listen_address}"))?;
+        let addr = listener.local_addr()?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let state = Arc::new(State { route, sender });
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!("Failed to accept connection: {e}");
+                        continue;
+                    }
+                };
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let state = state.clone();
+                        async move { Ok::<_, Infallible>(state.route.route(req, &state.sender).await) }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        debug!("Connection is closed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            task,
+            receiver,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub async fn next(&mut self) -> Result<Interaction> {
+        match self.receiver.recv().await {
+            Some(interaction) => Ok(interaction),
+            None => bail!("Callback server was stopped"),
+        }
+    }
+}
+
+impl Drop for Callback {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::services::chat::Decision;
+
+    // Approves as the user in the path, like `/U1`
+    struct PathRoute;
+
+    impl Route for PathRoute {
+        async fn route(
+            &self,
+            req: Request<Incoming>,
+            sender: &mpsc::UnboundedSender<Interaction>,
+        ) -> Response<Full<Bytes>> {
+            let user_id = req.uri().path().trim_start_matches('/').to_string();
+            let _ = sender.send(Interaction {
+                mention: format!("@{user_id}"),
+                user_id,
+                channel_id: "C1".into(),
+                decision: Decision::Approved,
+            });
+            Response::new(Full::new(Bytes::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_close_port_when_dropped() {
+        let mut callback = Callback::listen("127.0.0.1:0", PathRoute).await.unwrap();
+        let url = format!("http://{}/U1", callback.addr());
+
+        let res = reqwest::Client::new().post(&url).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(callback.next().await.unwrap().user_id, "U1");
+
+        drop(callback);
+        tokio::task::yield_now().await;
+        assert!(reqwest::Client::new().post(&url).send().await.is_err());
+    }
+}
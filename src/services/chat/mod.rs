@@ -0,0 +1,506 @@
+use std::collections::HashSet;
+use std::future::Future;
+
+use anyhow::{Context, Result, bail};
+use ring::rand::{SecureRandom, SystemRandom};
+use tracing::{info, warn};
+
+use crate::services::github::file_commands;
+use crate::services::github::github_inputs::{DestroyPolicy, GitHubInputs, Mode};
+use crate::services::termination::{Termination, TerminationReason};
+use crate::services::terraform;
+
+pub mod callback;
+
+// Bytes of the secret which identifies the approval in the buttons
+const SECRET_LEN: usize = 16;
+
+// What someone decided with the buttons
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Decision {
+    Approved,
+    Rejected,
+}
+
+// Click on a button of a copy of the approval
+#[derive(PartialEq, Debug, Clone)]
+pub struct Interaction {
+    pub user_id: String,
+    // How the platform mentions the user, like `<@U0123>` in Slack
+    pub mention: String,
+    pub channel_id: String,
+    pub decision: Decision,
+}
+
+// What replaces the buttons once the approval ends
+#[derive(PartialEq, Debug, Clone)]
+pub enum Outcome {
+    Approved { mention: String },
+    Rejected { mention: String },
+    Cancelled,
+    TimedOut,
+}
+
+impl Outcome {
+    pub fn text(&self) -> String {
+        match self {
+            Outcome::Approved { mention } => format!("Approved by {mention}"),
+            Outcome::Rejected { mention } => format!("Rejected by {mention}"),
+            Outcome::Cancelled => "🚫Cancelled".into(),
+            Outcome::TimedOut => "⌛Timed out".into(),
+        }
+    }
+}
+
+// Chat platform which posts the approval and receives the clicks
+// NOTE: Each platform builds the approval itself, as what a message can show differs
+pub trait Transport {
+    // Posts a copy of the approval, which is kept to be updated later
+    fn post(&mut self, channel_id: &str) -> impl Future<Output = Result<()>>;
+    // Replaces the buttons of every copy with the outcome
+    fn update(&self, outcome: &Outcome) -> impl Future<Output = Result<()>>;
+    // Tells why the click did nothing, next to the copy which was clicked
+    fn notice(&self, interaction: &Interaction, text: &str) -> impl Future<Output = Result<()>>;
+    fn group_members(&self, group_id: &str) -> impl Future<Output = Result<Vec<String>>>;
+    // Waits for the next click on the approval or the reject button
+    fn next_interaction(&mut self) -> impl Future<Output = Result<Interaction>>;
+}
+
+// Who can decide
+#[derive(PartialEq, Debug)]
+pub struct Authorization {
+    users: Vec<String>,
+    // Anyone can decide when neither users nor groups are specified
+    should_authorize: bool,
+}
+
+impl Authorization {
+    pub async fn collect<T: Transport>(
+        transport: &T,
+        users: &[String],
+        groups: &[String],
+    ) -> Result<Self> {
+        let mut authorized_users = users.to_vec();
+        for group in groups {
+            let members = transport.group_members(group).await?;
+            info!("User IDs from group {group}: {:?}", members);
+            authorized_users.extend(members);
+        }
+
+        // Remove duplicates
+        let mut hash_set = HashSet::new();
+        authorized_users.retain(|e| hash_set.insert(e.clone()));
+
+        info!("Authorized users: {:?}", authorized_users);
+
+        Ok(Self {
+            users: authorized_users,
+            should_authorize: !users.is_empty() || !groups.is_empty(),
+        })
+    }
+
+    pub fn users(&self) -> &[String] {
+        &self.users
+    }
+
+    pub fn should_authorize(&self) -> bool {
+        self.should_authorize
+    }
+
+    fn is_authorized(&self, user_id: &str) -> bool {
+        if !self.should_authorize {
+            info!("Authorization skipped.");
+            return true;
+        }
+
+        if self.users.is_empty() {
+            info!("No authorized users.");
+            return false;
+        }
+
+        self.users.iter().any(|user| user == user_id)
+    }
+}
+
+// Inputs which only Slack supports
+pub fn warn_unsupported_inputs(github_inputs: &GitHubInputs, platform: &str) {
+    let unsupported = [
+        ("template-file", github_inputs.template_file.is_some()),
+        ("commits-base", github_inputs.commits_base.is_some()),
+        ("attachments", !github_inputs.attachments.is_empty()),
+        ("thread-ts", github_inputs.thread_ts.is_some()),
+        ("thread-match", github_inputs.thread_match.is_some()),
+        ("supersede-pending", github_inputs.supersede_pending),
+        ("join-channel", github_inputs.join_channel),
+        ("post-to", github_inputs.post_to.direct_message),
+    ];
+    for (name, is_set) in unsupported {
+        if is_set {
+            warn!("Input '{name}' is not supported on {platform} and is ignored");
+        }
+    }
+}
+
+// NOTE: The plan is not shown, but the policy still stops destroying without confirmation
+pub fn check_destroy_policy(github_inputs: &GitHubInputs, platform: &str) -> Result<()> {
+    let plan = github_inputs
+        .terraform_plan
+        .as_deref()
+        .map(terraform::read_plan)
+        .transpose()?;
+    if !plan.is_some_and(|plan| plan.has_destroys()) {
+        return Ok(());
+    }
+
+    match github_inputs.terraform_destroy {
+        DestroyPolicy::Allow => Ok(()),
+        DestroyPolicy::Confirm => bail!(
+            "Terraform plan destroys resources, and `terraform-destroy: confirm` is not supported on {platform}"
+        ),
+        DestroyPolicy::Deny => {
+            bail!("Terraform plan destroys resources, and `terraform-destroy` is `deny`")
+        }
+    }
+}
+
+// Tells the clicks on this approval from others
+pub fn generate_secret() -> Result<String> {
+    let mut bytes = [0u8; SECRET_LEN];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow::anyhow!("Failed to generate secret"))?;
+
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+enum Event {
+    Interaction(Result<Interaction>),
+    Terminated(TerminationReason),
+}
+
+// Posts a copy to each channel
+// NOTE: When one fails, the copies posted before are marked as cancelled, as no one waits for their buttons
+pub async fn post_all<T: Transport>(transport: &mut T, channel_ids: &[String]) -> Result<()> {
+    for channel_id in channel_ids {
+        if let Err(e) = transport.post(channel_id).await {
+            cancel(transport).await;
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+// Updates the copies one by one, and fails with the last error
+// NOTE: Other copies are still updated when one fails
+pub async fn update_each(
+    updates: impl IntoIterator<Item = impl Future<Output = Result<()>>>,
+) -> Result<()> {
+    let mut result = Ok(());
+    for update in updates {
+        if let Err(e) = update.await {
+            warn!("{e:#}");
+            result = Err(e);
+        }
+    }
+
+    result
+}
+
+// Removes the buttons when the step fails before waiting for a decision
+pub async fn cancel<T: Transport>(transport: &T) {
+    if let Err(e) = transport.update(&Outcome::Cancelled).await {
+        warn!("Failed to mark approval as cancelled: {e:#}");
+    }
+}
+
+// Posts the approval and waits for a decision, for the platforms which receive the clicks on a callback
+// NOTE: The callback is started beforehand, as clicks right after posting would be lost otherwise
+pub async fn run_approval<T: Transport>(
+    mut transport: T,
+    github_inputs: &GitHubInputs,
+    mut termination: Termination,
+    outputs: impl FnOnce(&T) -> Result<Vec<(&'static str, String)>>,
+) -> Result<()> {
+    let authorization = Authorization::collect(
+        &transport,
+        &github_inputs.authorized_users,
+        &github_inputs.authorized_groups,
+    )
+    .await
+    .with_context(|| "Failed to collect authorized users")?;
+
+    post_all(&mut transport, &github_inputs.channel_ids).await?;
+    if let Err(e) = outputs(&transport).and_then(|outputs| file_commands::set_outputs(&outputs)) {
+        cancel(&transport).await;
+        return Err(e);
+    }
+    if github_inputs.mode == Mode::Notify {
+        info!("Posted without waiting for approval");
+        return Ok(());
+    }
+
+    match wait_for_decision(&mut transport, &authorization, &mut termination).await? {
+        Decision::Approved => Ok(()),
+        Decision::Rejected => bail!("Approval was rejected"),
+    }
+}
+
+// Until an authorized user decides, or the approval is cancelled or times out
+pub async fn wait_for_decision<T: Transport>(
+    transport: &mut T,
+    authorization: &Authorization,
+    termination: &mut Termination,
+) -> Result<Decision> {
+    loop {
+        let event = tokio::select! {
+            interaction = transport.next_interaction() => Event::Interaction(interaction),
+            reason = termination.wait() => Event::Terminated(reason),
+        };
+
+        let interaction = match event {
+            Event::Interaction(Ok(interaction)) => interaction,
+            // NOTE: Nobody can approve once the connection is lost, so the buttons are closed
+            Event::Interaction(Err(e)) => {
+                cancel(transport).await;
+                return Err(e);
+            }
+            Event::Terminated(reason) => {
+                let outcome = match reason {
+                    TerminationReason::Cancelled => Outcome::Cancelled,
+                    TerminationReason::TimedOut => Outcome::TimedOut,
+                };
+                transport.update(&outcome).await?;
+                match reason {
+                    TerminationReason::Cancelled => bail!("Approval was cancelled"),
+                    TerminationReason::TimedOut => {
+                        bail!("No one approved or rejected within timeout")
+                    }
+                }
+            }
+        };
+
+        let (verb, label, outcome) = match interaction.decision {
+            Decision::Approved => (
+                "approve",
+                "Approve",
+                Outcome::Approved {
+                    mention: interaction.mention.clone(),
+                },
+            ),
+            Decision::Rejected => (
+                "reject",
+                "Reject",
+                Outcome::Rejected {
+                    mention: interaction.mention.clone(),
+                },
+            ),
+        };
+        info!("{label} button clicked by: {}", interaction.user_id);
+
+        if !authorization.is_authorized(&interaction.user_id) {
+            info!("User is not authorized to {verb}: {}", interaction.user_id);
+            let text = format!(
+                "You are not authorized to {verb} this action: {}",
+                interaction.user_id
+            );
+            if let Err(e) = transport.notice(&interaction, &text).await {
+                warn!("{:?}", e);
+            }
+            continue;
+        }
+
+        info!("User is authorized to {verb}: {}", interaction.user_id);
+        // NOTE: The decision stands even if the message is not updated
+        if let Err(e) = transport.update(&outcome).await {
+            warn!("{:?}", e);
+        }
+
+        return Ok(interaction.decision);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("U1", vec![], false, true)]
+    #[case("U1", vec![], true, false)]
+    #[case("U1", vec!["U2".into(), "U3".into()], true, false)]
+    #[case("U1", vec!["U2".into(), "U1".into()], true, true)]
+    fn test_is_authorized_user(
+        #[case] user_id: &str,
+        #[case] users: Vec<String>,
+        #[case] should_authorize: bool,
+        #[case] expected: bool,
+    ) {
+        let authorization = Authorization {
+            users,
+            should_authorize,
+        };
+
+        assert_eq!(authorization.is_authorized(user_id), expected);
+    }
+
+    // Clicks in order, and records what is sent back
+    #[derive(Default)]
+    struct FakeTransport {
+        interactions: VecDeque<Interaction>,
+        groups: Vec<(String, Vec<String>)>,
+        posts: Vec<String>,
+        failing_channel_id: Option<String>,
+        disconnected: bool,
+        updates: Mutex<Vec<Outcome>>,
+        notices: Mutex<Vec<String>>,
+    }
+
+    impl Transport for FakeTransport {
+        async fn post(&mut self, channel_id: &str) -> Result<()> {
+            if self.failing_channel_id.as_deref() == Some(channel_id) {
+                bail!("Failed to post to {channel_id}");
+            }
+            self.posts.push(channel_id.into());
+            Ok(())
+        }
+
+        async fn update(&self, outcome: &Outcome) -> Result<()> {
+            self.updates.lock().unwrap().push(outcome.clone());
+            Ok(())
+        }
+
+        async fn notice(&self, _interaction: &Interaction, text: &str) -> Result<()> {
+            self.notices.lock().unwrap().push(text.into());
+            Ok(())
+        }
+
+        async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+            Ok(self
+                .groups
+                .iter()
+                .find(|(id, _)| id == group_id)
+                .map(|(_, users)| users.clone())
+                .unwrap_or_default())
+        }
+
+        async fn next_interaction(&mut self) -> Result<Interaction> {
+            match self.interactions.pop_front() {
+                Some(interaction) => Ok(interaction),
+                None if self.disconnected => bail!("Connection closed"),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    fn click(user_id: &str, decision: Decision) -> Interaction {
+        Interaction {
+            user_id: user_id.into(),
+            mention: format!("@{user_id}"),
+            channel_id: "C1".into(),
+            decision,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_collect_authorized_users() {
+        let transport = FakeTransport {
+            groups: vec![("S1".into(), vec!["U2".into(), "U3".into()])],
+            ..Default::default()
+        };
+
+        let actual =
+            Authorization::collect(&transport, &["U1".into(), "U2".into()], &["S1".into()])
+                .await
+                .unwrap();
+
+        assert_eq!(actual.users(), ["U1", "U2", "U3"]);
+        assert!(actual.should_authorize());
+    }
+
+    #[test]
+    fn should_generate_random_secret() {
+        let secret = generate_secret().unwrap();
+
+        assert_eq!(secret.len(), SECRET_LEN * 2);
+        assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(secret, generate_secret().unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_cancel_posted_copies_when_post_fails() {
+        let mut transport = FakeTransport {
+            failing_channel_id: Some("C2".into()),
+            ..Default::default()
+        };
+
+        let actual = post_all(&mut transport, &["C1".into(), "C2".into(), "C3".into()]).await;
+
+        assert!(actual.is_err());
+        assert_eq!(transport.posts, ["C1"]);
+        assert_eq!(*transport.updates.lock().unwrap(), vec![Outcome::Cancelled]);
+    }
+
+    #[tokio::test]
+    async fn should_decide_by_authorized_user() {
+        let mut transport = FakeTransport {
+            interactions: VecDeque::from([
+                click("U2", Decision::Approved),
+                click("U1", Decision::Rejected),
+            ]),
+            ..Default::default()
+        };
+        let authorization = Authorization::collect(&transport, &["U1".into()], &[])
+            .await
+            .unwrap();
+        let mut termination = Termination::install(None).unwrap();
+
+        let actual = wait_for_decision(&mut transport, &authorization, &mut termination)
+            .await
+            .unwrap();
+
+        assert_eq!(actual, Decision::Rejected);
+        assert_eq!(
+            *transport.notices.lock().unwrap(),
+            vec!["You are not authorized to approve this action: U2".to_string()]
+        );
+        assert_eq!(
+            *transport.updates.lock().unwrap(),
+            vec![Outcome::Rejected {
+                mention: "@U1".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_cancel_when_connection_is_lost() {
+        let mut transport = FakeTransport {
+            disconnected: true,
+            ..Default::default()
+        };
+        let authorization = Authorization::collect(&transport, &[], &[]).await.unwrap();
+        let mut termination = Termination::install(None).unwrap();
+
+        let actual = wait_for_decision(&mut transport, &authorization, &mut termination).await;
+
+        assert!(actual.is_err());
+        assert_eq!(*transport.updates.lock().unwrap(), vec![Outcome::Cancelled]);
+    }
+
+    #[tokio::test]
+    async fn should_time_out_without_decision() {
+        let mut transport = FakeTransport::default();
+        let authorization = Authorization::collect(&transport, &[], &[]).await.unwrap();
+        let mut termination = Termination::install(Some(Duration::from_millis(10))).unwrap();
+
+        let actual = wait_for_decision(&mut transport, &authorization, &mut termination).await;
+
+        assert!(actual.is_err());
+        assert_eq!(*transport.updates.lock().unwrap(), vec![Outcome::TimedOut]);
+    }
+}
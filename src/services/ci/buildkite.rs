@@ -0,0 +1,134 @@
+use super::{CiInfo, Labels, to_repository, to_web_url};
+
+// https://buildkite.com/docs/pipelines/configure/environment-variables
+#[derive(Debug, PartialEq)]
+pub struct BuildkiteInfo {
+    repository_url: Option<String>,
+    repository: String,
+    build_id: String,
+    build_number: String,
+    build_url: Option<String>,
+    pipeline_slug: String,
+    label: String,
+    build_creator: String,
+    agent_name: String,
+    commit: String,
+    branch: String,
+    checkout_path: Option<String>,
+}
+
+impl BuildkiteInfo {
+    pub fn read(var: &dyn Fn(&str) -> Option<String>) -> Self {
+        let value = |name: &str| var(name).unwrap_or_default();
+        let repository_url = var("BUILDKITE_REPO").and_then(|remote| to_web_url(&remote));
+        Self {
+            repository: repository_url
+                .as_deref()
+                .map(to_repository)
+                .unwrap_or_default(),
+            repository_url,
+            build_id: value("BUILDKITE_BUILD_ID"),
+            build_number: value("BUILDKITE_BUILD_NUMBER"),
+            build_url: var("BUILDKITE_BUILD_URL"),
+            pipeline_slug: value("BUILDKITE_PIPELINE_SLUG"),
+            label: value("BUILDKITE_LABEL"),
+            build_creator: value("BUILDKITE_BUILD_CREATOR"),
+            agent_name: value("BUILDKITE_AGENT_NAME"),
+            commit: value("BUILDKITE_COMMIT"),
+            branch: var("BUILDKITE_TAG")
+                .or_else(|| var("BUILDKITE_BRANCH"))
+                .unwrap_or_default(),
+            checkout_path: var("BUILDKITE_BUILD_CHECKOUT_PATH"),
+        }
+    }
+}
+
+impl CiInfo for BuildkiteInfo {
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Build",
+            workflow: "Pipeline",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.build_creator
+    }
+
+    fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        self.repository_url.clone()
+    }
+
+    fn run_id(&self) -> &str {
+        &self.build_id
+    }
+
+    fn run_number(&self) -> &str {
+        &self.build_number
+    }
+
+    fn run_url(&self) -> Option<String> {
+        self.build_url.clone()
+    }
+
+    fn workflow(&self) -> &str {
+        &self.pipeline_slug
+    }
+
+    fn job(&self) -> &str {
+        &self.label
+    }
+
+    fn runner(&self) -> &str {
+        &self.agent_name
+    }
+
+    fn sha(&self) -> &str {
+        &self.commit
+    }
+
+    fn ref_name(&self) -> &str {
+        &self.branch
+    }
+
+    fn workspace(&self) -> Option<&str> {
+        self.checkout_path.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn should_read_buildkite_info() {
+        let vars = HashMap::from([
+            ("BUILDKITE_REPO", "git@github.com:octocat/Hello-World.git"),
+            ("BUILDKITE_BUILD_ID", "f62a1b4d-10f9-4790-bc1c-e2c3a0c80983"),
+            ("BUILDKITE_BUILD_NUMBER", "42"),
+            (
+                "BUILDKITE_BUILD_URL",
+                "https://buildkite.com/acme/deploy/builds/42",
+            ),
+            ("BUILDKITE_PIPELINE_SLUG", "deploy"),
+            ("BUILDKITE_BRANCH", "main"),
+            ("BUILDKITE_TAG", "v1.42.0"),
+        ]);
+
+        let actual = BuildkiteInfo::read(&|name| vars.get(name).map(|v| v.to_string()));
+
+        assert_eq!(actual.repository(), "octocat/Hello-World");
+        assert_eq!(
+            actual.repository_url().as_deref(),
+            Some("https://github.com/octocat/Hello-World")
+        );
+        assert_eq!(actual.ref_name(), "v1.42.0");
+        assert_eq!(actual.workflow(), "deploy");
+    }
+}
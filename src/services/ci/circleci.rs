@@ -0,0 +1,132 @@
+use super::{CiInfo, Labels, to_web_url};
+
+// https://circleci.com/docs/reference/variables/
+#[derive(Debug, PartialEq)]
+pub struct CircleCiInfo {
+    repository_url: Option<String>,
+    repository: String,
+    workflow_id: String,
+    build_num: String,
+    build_url: Option<String>,
+    job: String,
+    username: String,
+    commit: String,
+    branch: String,
+    working_directory: Option<String>,
+}
+
+impl CircleCiInfo {
+    pub fn read(var: &dyn Fn(&str) -> Option<String>) -> Self {
+        let value = |name: &str| var(name).unwrap_or_default();
+        Self {
+            repository_url: var("CIRCLE_REPOSITORY_URL").and_then(|remote| to_web_url(&remote)),
+            repository: format!(
+                "{}/{}",
+                value("CIRCLE_PROJECT_USERNAME"),
+                value("CIRCLE_PROJECT_REPONAME")
+            ),
+            workflow_id: value("CIRCLE_WORKFLOW_ID"),
+            build_num: value("CIRCLE_BUILD_NUM"),
+            build_url: var("CIRCLE_BUILD_URL"),
+            job: value("CIRCLE_JOB"),
+            username: value("CIRCLE_USERNAME"),
+            commit: value("CIRCLE_SHA1"),
+            branch: var("CIRCLE_TAG")
+                .or_else(|| var("CIRCLE_BRANCH"))
+                .unwrap_or_default(),
+            working_directory: var("CIRCLE_WORKING_DIRECTORY"),
+        }
+    }
+}
+
+impl CiInfo for CircleCiInfo {
+    // NOTE: The name of the workflow is not in the environment, so the job stands for it
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Build",
+            workflow: "Job",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.username
+    }
+
+    fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        self.repository_url.clone()
+    }
+
+    fn run_id(&self) -> &str {
+        &self.workflow_id
+    }
+
+    fn run_number(&self) -> &str {
+        &self.build_num
+    }
+
+    fn run_url(&self) -> Option<String> {
+        self.build_url.clone()
+    }
+
+    fn workflow(&self) -> &str {
+        &self.job
+    }
+
+    fn job(&self) -> &str {
+        ""
+    }
+
+    fn runner(&self) -> &str {
+        "CircleCI"
+    }
+
+    fn sha(&self) -> &str {
+        &self.commit
+    }
+
+    fn ref_name(&self) -> &str {
+        &self.branch
+    }
+
+    // NOTE: The directory may start with `~`, which git cannot open
+    fn workspace(&self) -> Option<&str> {
+        self.working_directory
+            .as_deref()
+            .filter(|dir| !dir.starts_with('~'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn should_read_circleci_info() {
+        let vars = HashMap::from([
+            (
+                "CIRCLE_REPOSITORY_URL",
+                "git@github.com:octocat/Hello-World.git",
+            ),
+            ("CIRCLE_PROJECT_USERNAME", "octocat"),
+            ("CIRCLE_PROJECT_REPONAME", "Hello-World"),
+            ("CIRCLE_BUILD_NUM", "42"),
+            ("CIRCLE_JOB", "deploy"),
+            ("CIRCLE_USERNAME", "octocat"),
+            ("CIRCLE_BRANCH", "main"),
+            ("CIRCLE_WORKING_DIRECTORY", "~/project"),
+        ]);
+
+        let actual = CircleCiInfo::read(&|name| vars.get(name).map(|v| v.to_string()));
+
+        assert_eq!(actual.repository(), "octocat/Hello-World");
+        assert_eq!(actual.workflow(), "deploy");
+        assert_eq!(actual.ref_name(), "main");
+        assert_eq!(actual.workspace(), None);
+    }
+}
@@ -0,0 +1,144 @@
+use super::{CiInfo, Labels};
+
+// https://docs.gitlab.com/ci/variables/predefined_variables/
+#[derive(Debug, PartialEq)]
+pub struct GitLabInfo {
+    project_url: Option<String>,
+    project_path: String,
+    pipeline_id: String,
+    pipeline_iid: String,
+    pipeline_url: Option<String>,
+    stage: String,
+    job_name: String,
+    user_login: String,
+    runner_description: String,
+    commit_sha: String,
+    commit_ref_name: String,
+    project_dir: Option<String>,
+}
+
+impl GitLabInfo {
+    pub fn read(var: &dyn Fn(&str) -> Option<String>) -> Self {
+        let value = |name: &str| var(name).unwrap_or_default();
+        Self {
+            project_url: var("CI_PROJECT_URL"),
+            project_path: value("CI_PROJECT_PATH"),
+            pipeline_id: value("CI_PIPELINE_ID"),
+            pipeline_iid: value("CI_PIPELINE_IID"),
+            pipeline_url: var("CI_PIPELINE_URL"),
+            stage: value("CI_JOB_STAGE"),
+            job_name: value("CI_JOB_NAME"),
+            user_login: value("GITLAB_USER_LOGIN"),
+            runner_description: value("CI_RUNNER_DESCRIPTION"),
+            commit_sha: value("CI_COMMIT_SHA"),
+            commit_ref_name: value("CI_COMMIT_REF_NAME"),
+            project_dir: var("CI_PROJECT_DIR"),
+        }
+    }
+}
+
+impl CiInfo for GitLabInfo {
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Pipeline",
+            workflow: "Stage",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.user_login
+    }
+
+    fn repository(&self) -> &str {
+        &self.project_path
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        self.project_url.clone()
+    }
+
+    fn run_id(&self) -> &str {
+        &self.pipeline_id
+    }
+
+    // NOTE: The IID counts up in the project like the run number of GitHub Actions
+    fn run_number(&self) -> &str {
+        &self.pipeline_iid
+    }
+
+    fn run_url(&self) -> Option<String> {
+        self.pipeline_url.clone()
+    }
+
+    fn workflow(&self) -> &str {
+        &self.stage
+    }
+
+    fn job(&self) -> &str {
+        &self.job_name
+    }
+
+    fn runner(&self) -> &str {
+        &self.runner_description
+    }
+
+    fn sha(&self) -> &str {
+        &self.commit_sha
+    }
+
+    fn ref_name(&self) -> &str {
+        &self.commit_ref_name
+    }
+
+    fn workspace(&self) -> Option<&str> {
+        self.project_dir.as_deref()
+    }
+
+    fn commit_url(&self, sha: &str) -> Option<String> {
+        self.project_url
+            .as_ref()
+            .map(|url| format!("{url}/-/commit/{sha}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn should_read_gitlab_info() {
+        let vars = HashMap::from([
+            ("CI_PROJECT_URL", "https://gitlab.com/group/project"),
+            ("CI_PROJECT_PATH", "group/project"),
+            ("CI_PIPELINE_ID", "1234567"),
+            ("CI_PIPELINE_IID", "42"),
+            (
+                "CI_PIPELINE_URL",
+                "https://gitlab.com/group/project/-/pipelines/1234567",
+            ),
+            ("CI_JOB_STAGE", "deploy"),
+            ("CI_JOB_NAME", "deploy-prod"),
+            ("GITLAB_USER_LOGIN", "octocat"),
+            ("CI_COMMIT_SHA", "ffac537e6cbbf934b08745a378932722df287a53"),
+            ("CI_COMMIT_REF_NAME", "main"),
+        ]);
+
+        let actual = GitLabInfo::read(&|name| vars.get(name).map(|v| v.to_string()));
+
+        assert_eq!(actual.actor(), "octocat");
+        assert_eq!(actual.run_number(), "42");
+        assert_eq!(
+            actual.run_url().as_deref(),
+            Some("https://gitlab.com/group/project/-/pipelines/1234567")
+        );
+        assert_eq!(
+            actual.commit_url(actual.sha()).as_deref(),
+            Some(
+                "https://gitlab.com/group/project/-/commit/ffac537e6cbbf934b08745a378932722df287a53"
+            )
+        );
+        assert_eq!(actual.short_sha(), "ffac537");
+    }
+}
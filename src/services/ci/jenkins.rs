@@ -0,0 +1,139 @@
+use super::{CiInfo, Labels, to_repository, to_web_url};
+
+// https://www.jenkins.io/doc/book/pipeline/jenkinsfile/#using-environment-variables
+// `GIT_*` are set by the Git plugin
+#[derive(Debug, PartialEq)]
+pub struct JenkinsInfo {
+    repository_url: Option<String>,
+    repository: String,
+    build_tag: String,
+    build_number: String,
+    build_url: Option<String>,
+    job_name: String,
+    stage_name: String,
+    user: String,
+    node_name: String,
+    commit: String,
+    branch: String,
+    workspace: Option<String>,
+}
+
+impl JenkinsInfo {
+    pub fn read(var: &dyn Fn(&str) -> Option<String>) -> Self {
+        let value = |name: &str| var(name).unwrap_or_default();
+        let repository_url = var("GIT_URL").and_then(|remote| to_web_url(&remote));
+        Self {
+            repository: repository_url
+                .as_deref()
+                .map(to_repository)
+                .unwrap_or_default(),
+            repository_url,
+            build_tag: value("BUILD_TAG"),
+            build_number: value("BUILD_NUMBER"),
+            build_url: var("BUILD_URL"),
+            job_name: value("JOB_NAME"),
+            stage_name: value("STAGE_NAME"),
+            // NOTE: `BUILD_USER_ID` needs the Build User Vars plugin
+            user: var("BUILD_USER_ID")
+                .or_else(|| var("CHANGE_AUTHOR"))
+                .unwrap_or_default(),
+            node_name: value("NODE_NAME"),
+            commit: value("GIT_COMMIT"),
+            branch: var("TAG_NAME")
+                .or_else(|| var("BRANCH_NAME"))
+                .or_else(|| {
+                    var("GIT_BRANCH").map(|branch| branch.trim_start_matches("origin/").to_string())
+                })
+                .unwrap_or_default(),
+            workspace: var("WORKSPACE"),
+        }
+    }
+}
+
+impl CiInfo for JenkinsInfo {
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Build",
+            workflow: "Job",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.user
+    }
+
+    fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        self.repository_url.clone()
+    }
+
+    // NOTE: The tag is `jenkins-<job>-<number>`, which is unique across the jobs
+    fn run_id(&self) -> &str {
+        &self.build_tag
+    }
+
+    fn run_number(&self) -> &str {
+        &self.build_number
+    }
+
+    fn run_url(&self) -> Option<String> {
+        self.build_url.clone()
+    }
+
+    fn workflow(&self) -> &str {
+        &self.job_name
+    }
+
+    fn job(&self) -> &str {
+        &self.stage_name
+    }
+
+    fn runner(&self) -> &str {
+        &self.node_name
+    }
+
+    fn sha(&self) -> &str {
+        &self.commit
+    }
+
+    fn ref_name(&self) -> &str {
+        &self.branch
+    }
+
+    fn workspace(&self) -> Option<&str> {
+        self.workspace.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn should_read_jenkins_info() {
+        let vars = HashMap::from([
+            ("GIT_URL", "https://github.com/octocat/Hello-World.git"),
+            ("BUILD_TAG", "jenkins-deploy-42"),
+            ("BUILD_NUMBER", "42"),
+            ("BUILD_URL", "https://jenkins.example.com/job/deploy/42/"),
+            ("JOB_NAME", "deploy"),
+            ("STAGE_NAME", "Approval"),
+            ("GIT_BRANCH", "origin/main"),
+        ]);
+
+        let actual = JenkinsInfo::read(&|name| vars.get(name).map(|v| v.to_string()));
+
+        assert_eq!(actual.repository(), "octocat/Hello-World");
+        assert_eq!(actual.ref_name(), "main");
+        assert_eq!(actual.job(), "Approval");
+        assert_eq!(
+            actual.run_url().as_deref(),
+            Some("https://jenkins.example.com/job/deploy/42/")
+        );
+    }
+}
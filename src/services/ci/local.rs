@@ -0,0 +1,74 @@
+use super::{CiInfo, Labels};
+
+// Run outside CI like a local release script or a cron job
+#[derive(Debug, PartialEq)]
+pub struct LocalInfo {
+    user: String,
+    host: String,
+}
+
+impl LocalInfo {
+    pub fn read(var: &dyn Fn(&str) -> Option<String>) -> Self {
+        Self {
+            user: var("USER").or_else(|| var("USERNAME")).unwrap_or_default(),
+            host: var("HOSTNAME").unwrap_or_default(),
+        }
+    }
+}
+
+impl CiInfo for LocalInfo {
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Run",
+            workflow: "Workflow",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.user
+    }
+
+    fn repository(&self) -> &str {
+        ""
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        None
+    }
+
+    fn run_id(&self) -> &str {
+        ""
+    }
+
+    fn run_number(&self) -> &str {
+        ""
+    }
+
+    fn run_url(&self) -> Option<String> {
+        None
+    }
+
+    fn workflow(&self) -> &str {
+        ""
+    }
+
+    fn job(&self) -> &str {
+        ""
+    }
+
+    fn runner(&self) -> &str {
+        &self.host
+    }
+
+    fn sha(&self) -> &str {
+        ""
+    }
+
+    fn ref_name(&self) -> &str {
+        ""
+    }
+
+    fn workspace(&self) -> Option<&str> {
+        None
+    }
+}
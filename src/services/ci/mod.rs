@@ -0,0 +1,157 @@
+use crate::services::github::github_info::{self, GitHubInfo};
+
+mod buildkite;
+mod circleci;
+mod gitlab;
+mod jenkins;
+mod local;
+
+// Names of the fields which differ between the CIs
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Labels {
+    // Link to the run like "Action" or "Pipeline"
+    pub run: &'static str,
+    // What the run belongs to like "Workflow" or "Stage"
+    pub workflow: &'static str,
+}
+
+// Context of the run which requests the approval
+// Values are empty when the CI does not provide them
+pub trait CiInfo: Send + Sync {
+    fn labels(&self) -> Labels;
+    fn actor(&self) -> &str;
+    // Path like `octocat/Hello-World`
+    fn repository(&self) -> &str;
+    fn repository_url(&self) -> Option<String>;
+    // Unique among the runs of the repository
+    fn run_id(&self) -> &str;
+    fn run_attempt(&self) -> Option<&str> {
+        None
+    }
+    fn run_number(&self) -> &str;
+    fn run_url(&self) -> Option<String>;
+    fn workflow(&self) -> &str;
+    fn job(&self) -> &str;
+    fn runner(&self) -> &str;
+    fn sha(&self) -> &str;
+    fn ref_name(&self) -> &str;
+    // Directory the repository is checked out in
+    fn workspace(&self) -> Option<&str>;
+
+    fn commit_url(&self, sha: &str) -> Option<String> {
+        self.repository_url()
+            .map(|url| format!("{url}/commit/{sha}"))
+    }
+
+    fn short_sha(&self) -> &str {
+        self.sha().get(..7).unwrap_or(self.sha())
+    }
+
+    // Event payload and the other values only GitHub Actions provides
+    fn github(&self) -> Option<&GitHubInfo> {
+        None
+    }
+}
+
+// Detects the CI from the variables each CI sets, and falls back to a local run
+pub fn detect_ci_info() -> Box<dyn CiInfo> {
+    detect(&optional_env)
+}
+
+fn detect(var: &dyn Fn(&str) -> Option<String>) -> Box<dyn CiInfo> {
+    let is_true = |name: &str| var(name).as_deref() == Some("true");
+    if is_true("GITHUB_ACTIONS") {
+        Box::new(github_info::read_github_info())
+    } else if is_true("GITLAB_CI") {
+        Box::new(gitlab::GitLabInfo::read(var))
+    } else if is_true("BUILDKITE") {
+        Box::new(buildkite::BuildkiteInfo::read(var))
+    } else if is_true("CIRCLECI") {
+        Box::new(circleci::CircleCiInfo::read(var))
+    } else if var("JENKINS_URL").is_some() {
+        Box::new(jenkins::JenkinsInfo::read(var))
+    } else {
+        Box::new(local::LocalInfo::read(var))
+    }
+}
+
+pub fn optional_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+// Web URL of a remote like `git@github.com:octocat/Hello-World.git`
+fn to_web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let (host, path) = if let Some(rest) = remote.strip_prefix("https://") {
+        rest.split_once('/')?
+    } else if let Some(rest) = remote.strip_prefix("ssh://") {
+        let rest = rest.split_once('@').map_or(rest, |(_, rest)| rest);
+        let (host, path) = rest.split_once('/')?;
+        // NOTE: The port of SSH is not the port of the web
+        (host.split(':').next()?, path)
+    } else {
+        let rest = remote.split_once('@').map_or(remote, |(_, rest)| rest);
+        rest.split_once(':')?
+    };
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{host}/{path}"))
+}
+
+// Path of the repository like `octocat/Hello-World` in the web URL
+fn to_repository(web_url: &str) -> String {
+    web_url
+        .strip_prefix("https://")
+        .and_then(|rest| rest.split_once('/'))
+        .map(|(_, path)| path.into())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "git@github.com:octocat/Hello-World.git",
+        Some("https://github.com/octocat/Hello-World")
+    )]
+    #[case(
+        "https://gitlab.com/group/sub/project.git",
+        Some("https://gitlab.com/group/sub/project")
+    )]
+    #[case(
+        "ssh://git@git.example.com:2222/team/app.git",
+        Some("https://git.example.com/team/app")
+    )]
+    #[case("/var/repos/app.git", None)]
+    fn test_to_web_url(#[case] remote: &str, #[case] expected: Option<&str>) {
+        assert_eq!(to_web_url(remote).as_deref(), expected);
+    }
+
+    #[test]
+    fn should_get_repository_from_web_url() {
+        assert_eq!(
+            to_repository("https://gitlab.com/group/sub/project"),
+            "group/sub/project"
+        );
+    }
+
+    #[rstest]
+    #[case(vec![("GITLAB_CI", "true")], "Pipeline")]
+    #[case(vec![("BUILDKITE", "true")], "Build")]
+    #[case(vec![("CIRCLECI", "true")], "Build")]
+    #[case(vec![("JENKINS_URL", "https://jenkins.example.com/")], "Build")]
+    #[case(vec![], "Run")]
+    fn test_detect(#[case] vars: Vec<(&str, &str)>, #[case] expected: &str) {
+        let vars: HashMap<&str, &str> = vars.into_iter().collect();
+        let ci_info = detect(&|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(ci_info.labels().run, expected);
+    }
+}
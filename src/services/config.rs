@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use serde_yaml::Value;
+
+const ENV_PREFIX: &str = "SLACK_APPROVAL_";
+const INPUT_PREFIX: &str = "INPUT_";
+
+// Where a value comes from. Earlier layers take precedence
+enum Layer {
+    // Command line flags or the config file
+    Values(HashMap<String, String>),
+    // `SLACK_APPROVAL_BOT_TOKEN` for `bot-token`
+    Env,
+    // `INPUT_BOT-TOKEN` for `bot-token`, which GitHub Actions sets for the inputs of the action
+    GitHubInputs,
+}
+
+impl Layer {
+    fn get(&self, name: &str) -> Option<String> {
+        match self {
+            Layer::Values(values) => values.get(name).cloned(),
+            Layer::Env => std::env::var(format!(
+                "{ENV_PREFIX}{}",
+                name.replace('-', "_").to_uppercase()
+            ))
+            .ok(),
+            Layer::GitHubInputs => std::env::var(format!(
+                "{INPUT_PREFIX}{}",
+                name.replace(' ', "_").to_uppercase()
+            ))
+            .ok(),
+        }
+    }
+}
+
+struct InputOptions {
+    pub required: bool,
+    pub trim_whitespace: bool,
+}
+
+// Options of a command, in the precedence of flags, environment variables, inputs of the action and the config file
+pub struct Config {
+    layers: Vec<Layer>,
+}
+
+impl Config {
+    pub fn load(flags: HashMap<String, String>) -> Result<Self> {
+        let mut layers = vec![Layer::Values(flags), Layer::Env];
+        // NOTE: Inputs of the action are ignored outside GitHub Actions so that stale variables do not leak in
+        if std::env::var("GITHUB_ACTIONS").as_deref() == Ok("true") {
+            layers.push(Layer::GitHubInputs);
+        }
+        let mut config = Self { layers };
+
+        if let Some(path) = config.get_optional("config")? {
+            let values = read_config_file(&path)?;
+            config.layers.push(Layer::Values(values));
+        }
+
+        Ok(config)
+    }
+
+    // NOTE: An empty value is treated as unset so that it does not hide the value of a later layer
+    fn get_input(&self, name: &str, options: &InputOptions) -> Result<Option<String>> {
+        let mut v = None;
+        for layer in &self.layers {
+            match layer.get(name) {
+                Some(value) if !value.trim().is_empty() => {
+                    v = Some(value);
+                    break;
+                }
+                Some(value) => {
+                    v.get_or_insert(value);
+                }
+                None => {}
+            }
+        }
+
+        let v = if options.trim_whitespace {
+            v.map(|v| v.trim().into())
+        } else {
+            v
+        };
+
+        if options.required {
+            if v.is_none() {
+                bail!("Input '{}' is required", name);
+            }
+            if let Some(v) = &v
+                && v.is_empty()
+            {
+                bail!("Input '{}' cannot be empty", name);
+            }
+        }
+
+        Ok(v)
+    }
+
+    // Values are separated by commas or line breaks
+    pub fn get_list(&self, name: &str) -> Result<Vec<String>> {
+        self.get_input(
+            name,
+            &InputOptions {
+                required: false,
+                trim_whitespace: true,
+            },
+        )
+        .map(|o| {
+            o.map(|v| {
+                v.split([',', '\n'])
+                    .map(|s| s.trim().into())
+                    .filter(|s: &String| !s.is_empty())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default()
+        })
+    }
+
+    // Each line is trimmed and empty lines are removed like `core.getMultilineInput` of actions/toolkit
+    pub fn get_multiline(&self, name: &str) -> Result<Vec<String>> {
+        self.get_input(
+            name,
+            &InputOptions {
+                required: false,
+                trim_whitespace: false,
+            },
+        )
+        .map(|o| {
+            o.map(|v| {
+                v.lines()
+                    .map(|s| s.trim().into())
+                    .filter(|s: &String| !s.is_empty())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default()
+        })
+    }
+
+    pub fn get_required_list(&self, name: &str) -> Result<Vec<String>> {
+        let v = self.get_list(name)?;
+        if v.is_empty() {
+            bail!("Input '{}' is required", name);
+        }
+
+        Ok(v)
+    }
+
+    // Returns None when the input is not set or empty
+    pub fn get_optional(&self, name: &str) -> Result<Option<String>> {
+        self.get_input(
+            name,
+            &InputOptions {
+                required: false,
+                trim_whitespace: true,
+            },
+        )
+        .map(|o| o.filter(|v| !v.is_empty()))
+    }
+
+    // Follows the YAML 1.2 "core schema" like `core.getBooleanInput` of actions/toolkit
+    // Returns false when the input is not set
+    pub fn get_bool(&self, name: &str) -> Result<bool> {
+        match self.get_optional(name)?.as_deref() {
+            None => Ok(false),
+            Some("true" | "True" | "TRUE") => Ok(true),
+            Some("false" | "False" | "FALSE") => Ok(false),
+            Some(_) => bail!(
+                "Input '{}' does not meet YAML 1.2 \"Core Schema\" specification. Support boolean input list: `true | True | TRUE | false | False | FALSE`",
+                name
+            ),
+        }
+    }
+
+    pub fn get_required(&self, name: &str) -> Result<String> {
+        match self.get_input(
+            name,
+            &InputOptions {
+                required: true,
+                trim_whitespace: true,
+            },
+        )? {
+            Some(v) => Ok(v),
+            None => unreachable!(),
+        }
+    }
+}
+
+// Keys are the names of the flags like `bot-token`
+fn read_config_file(path: &str) -> Result<HashMap<String, String>> {
+    let file =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read config: {path}"))?;
+    let values: HashMap<String, Value> =
+        serde_yaml::from_str(&file).with_context(|| format!("Failed to parse config: {path}"))?;
+
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let value = to_config_value(&value)
+                .with_context(|| format!("Invalid value of '{name}' in config: {path}"))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+// NOTE: A sequence becomes lines, which both list and multiline values accept
+fn to_config_value(value: &Value) -> Result<String> {
+    match value {
+        Value::Null => Ok("".into()),
+        Value::Bool(v) => Ok(v.to_string()),
+        Value::Number(v) => Ok(v.to_string()),
+        Value::String(v) => Ok(v.clone()),
+        Value::Sequence(values) => Ok(values
+            .iter()
+            .map(to_config_value)
+            .collect::<Result<Vec<String>>>()?
+            .join("\n")),
+        _ => bail!("Value must be a scalar or a sequence of scalars"),
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    pub fn from_values(values: &[(&str, &str)]) -> Self {
+        Self {
+            layers: vec![Layer::Values(
+                values
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            )],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    fn config(env_value: Option<&str>) -> Config {
+        Config::from_values(
+            &env_value
+                .map(|v| ("name", v))
+                .into_iter()
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[rstest]
+    #[case(
+        None,
+        InputOptions {
+            required: true,
+            trim_whitespace: true,
+        },
+        Err("Input 'name' is required".into())
+    )]
+    #[case(
+        Some(""),
+        InputOptions {
+            required: true,
+            trim_whitespace: true,
+        },
+        Err("Input 'name' cannot be empty".into())
+    )]
+    #[case(
+        Some(" va lue  "),
+        InputOptions {
+            required: true,
+            trim_whitespace: true,
+        },
+        Ok(Some("va lue".into()))
+    )]
+    #[case(
+        Some(" va lue  "),
+        InputOptions {
+            required: true,
+            trim_whitespace: false,
+        },
+        Ok(Some(" va lue  ".into())))
+    ]
+    #[case(
+        None,
+        InputOptions {
+            required: false,
+            trim_whitespace: false,
+        },
+        Ok(None)
+    )]
+    #[case(
+        Some(""),
+        InputOptions {
+            required: false,
+            trim_whitespace: false,
+        },
+        Ok(Some("".into()))
+    )]
+    #[case(
+        Some(" va lue  "),
+        InputOptions {
+            required: false,
+            trim_whitespace: true,
+        },
+        Ok(Some("va lue".into()))
+    )]
+    #[case(
+        Some(" va lue  "),
+        InputOptions {
+            required: false,
+            trim_whitespace: false,
+        },
+        Ok(Some(" va lue  ".into())))
+    ]
+    fn test_get_input(
+        #[case] env_value: Option<&str>,
+        #[case] options: InputOptions,
+        #[case] expected: Result<Option<String>, String>,
+    ) {
+        let actual = config(env_value).get_input("name", &options);
+        let actual = actual.map_err(|e| e.to_string());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Ok(vec![]))]
+    #[case(Some(""), Ok(vec![]))]
+    #[case(Some("v1, v2, v3"), Ok(vec!["v1".into(), "v2".into(), "v3".into()]))]
+    #[case(Some("v1, , v3"), Ok(vec!["v1".into(), "v3".into()]))]
+    #[case(Some("v1\nv2, v3"), Ok(vec!["v1".into(), "v2".into(), "v3".into()]))]
+    fn test_get_list(
+        #[case] env_value: Option<&str>,
+        #[case] expected: Result<Vec<String>, String>,
+    ) {
+        let actual = config(env_value).get_list("name");
+        let actual = actual.map_err(|e| e.to_string());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, vec![])]
+    #[case(Some("v1, v2\n\n  v3  \n"), vec!["v1, v2".into(), "v3".into()])]
+    fn test_get_multiline(#[case] env_value: Option<&str>, #[case] expected: Vec<String>) {
+        let actual = config(env_value).get_multiline("name").unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Err("Input 'name' is required".into()))]
+    #[case(Some(" , "), Err("Input 'name' is required".into()))]
+    #[case(Some("v1, v2"), Ok(vec!["v1".into(), "v2".into()]))]
+    fn test_get_required_list(
+        #[case] env_value: Option<&str>,
+        #[case] expected: Result<Vec<String>, String>,
+    ) {
+        let actual = config(env_value)
+            .get_required_list("name")
+            .map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, None)]
+    #[case(Some("  "), None)]
+    #[case(Some(" value "), Some("value".into()))]
+    fn test_get_optional(#[case] env_value: Option<&str>, #[case] expected: Option<String>) {
+        let actual = config(env_value).get_optional("name").unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Ok(false))]
+    #[case(Some(""), Ok(false))]
+    #[case(Some("true"), Ok(true))]
+    #[case(Some(" TRUE "), Ok(true))]
+    #[case(Some("False"), Ok(false))]
+    #[case(
+        Some("yes"),
+        Err("Input 'name' does not meet YAML 1.2 \"Core Schema\" specification. Support boolean input list: `true | True | TRUE | false | False | FALSE`".into())
+    )]
+    fn test_get_bool(#[case] env_value: Option<&str>, #[case] expected: Result<bool, String>) {
+        let actual = config(env_value)
+            .get_bool("name")
+            .map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Err("Input 'name' is required".into()))]
+    #[case(Some(""), Err("Input 'name' cannot be empty".into()))]
+    #[case(Some("value"), Ok("value".into()))]
+    fn test_get_required(
+        #[case] env_value: Option<&str>,
+        #[case] expected: Result<String, String>,
+    ) {
+        let actual = config(env_value)
+            .get_required("name")
+            .map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_get_value_in_order_of_precedence() {
+        unsafe {
+            std::env::set_var("SLACK_APPROVAL_CONFIG_TEST_FLAG", "env");
+            std::env::set_var("SLACK_APPROVAL_CONFIG_TEST_ENV", "env");
+            std::env::set_var("SLACK_APPROVAL_CONFIG_TEST_EMPTY", "");
+            std::env::set_var("INPUT_CONFIG-TEST-INPUT", "input");
+        }
+        let config = Config {
+            layers: vec![
+                Layer::Values(HashMap::from([("config-test-flag".into(), "flag".into())])),
+                Layer::Env,
+                Layer::GitHubInputs,
+                Layer::Values(HashMap::from([
+                    ("config-test-flag".into(), "file".into()),
+                    ("config-test-input".into(), "file".into()),
+                    ("config-test-empty".into(), "file".into()),
+                    ("config-test-file".into(), "file".into()),
+                ])),
+            ],
+        };
+
+        assert_eq!(config.get_required("config-test-flag").unwrap(), "flag");
+        assert_eq!(config.get_required("config-test-env").unwrap(), "env");
+        assert_eq!(config.get_required("config-test-input").unwrap(), "input");
+        assert_eq!(config.get_required("config-test-empty").unwrap(), "file");
+        assert_eq!(config.get_required("config-test-file").unwrap(), "file");
+    }
+
+    #[test]
+    fn should_read_config_file() {
+        let path = std::env::temp_dir().join("slack-approval-should-read-config-file.yml");
+        std::fs::write(
+            &path,
+            "bot-token: xoxb-bot-token\nchannel-id: [C1234567890, C0987654321]\napproval-timeout-minutes: 10\nsupersede-pending: true\n",
+        )
+        .unwrap();
+
+        let actual = read_config_file(&path.to_string_lossy()).unwrap();
+
+        assert_eq!(
+            actual,
+            HashMap::from([
+                ("bot-token".into(), "xoxb-bot-token".into()),
+                ("channel-id".into(), "C1234567890\nC0987654321".into()),
+                ("approval-timeout-minutes".into(), "10".into()),
+                ("supersede-pending".into(), "true".into()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use http::{Method, Request, Response, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::services::config::Config;
+use crate::services::termination::Termination;
+use workspace::{Click, Params, Workspace};
+
+mod socket;
+mod workspace;
+
+// Scopes of both tokens, so that `check` passes
+const SCOPES: &str = "chat:write,channels:join,channels:history,im:write,usergroups:read,files:write,connections:write";
+
+// Envelopes which are not sent yet to a slow app
+const EVENT_CAPACITY: usize = 16;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct FakeSlackOptions {
+    pub host: String,
+    pub port: u16,
+    // Group ID and its members
+    pub usergroups: Vec<(String, Vec<String>)>,
+}
+
+pub fn read_fake_slack_options(config: &Config) -> Result<FakeSlackOptions> {
+    let port = match config.get_optional("port")? {
+        Some(port) => port
+            .parse()
+            .with_context(|| format!("Invalid port: {port}"))?,
+        None => 3000,
+    };
+    let usergroups = config
+        .get_multiline("usergroups")?
+        .iter()
+        .map(|line| parse_usergroup(line))
+        .collect::<Result<_>>()?;
+
+    Ok(FakeSlackOptions {
+        host: config
+            .get_optional("host")?
+            .unwrap_or_else(|| "127.0.0.1".into()),
+        port,
+        usergroups,
+    })
+}
+
+// `S0123: U0123, U0456`
+fn parse_usergroup(line: &str) -> Result<(String, Vec<String>)> {
+    let Some((id, users)) = line.split_once(':') else {
+        bail!("Invalid usergroup. Expected `S0123: U0123, U0456`: {line}");
+    };
+    let users = users
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|user| !user.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok((id.trim().into(), users))
+}
+
+pub async fn handle_fake_slack(options: &FakeSlackOptions) -> Result<()> {
+    let mut termination = Termination::install(None)?;
+    let fake_slack = FakeSlack::start(options).await?;
+    info!(
+        "Fake Slack is listening. Set slack-api-url to {}",
+        fake_slack.api_url()
+    );
+
+    termination.wait().await;
+    fake_slack.shutdown();
+
+    Ok(())
+}
+
+struct State {
+    workspace: Mutex<Workspace>,
+    events: broadcast::Sender<String>,
+    envelopes: AtomicU64,
+}
+
+// The subset of the Web API and Socket Mode which slack-approval uses, and a control API to click buttons
+pub struct FakeSlack {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl FakeSlack {
+    pub async fn start(options: &FakeSlackOptions) -> Result<Self> {
+        let listener = TcpListener::bind((options.host.as_str(), options.port))
+            .await
+            .with_context(|| format!("Failed to listen on {}:{}", options.host, options.port))?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(State {
+            workspace: Mutex::new(Workspace::new(options.usergroups.clone())),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            envelopes: AtomicU64::new(0),
+        });
+
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!("Failed to accept connection: {e}");
+                        continue;
+                    }
+                };
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let state = state.clone();
+                        async move { Ok::<_, Infallible>(route(req, &state, addr).await) }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .with_upgrades()
+                        .await
+                    {
+                        debug!("Connection is closed: {e}");
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, task })
+    }
+
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+
+    pub fn shutdown(self) {
+        self.task.abort();
+    }
+}
+
+async fn route(
+    mut req: Request<Incoming>,
+    state: &State,
+    addr: SocketAddr,
+) -> Response<Full<Bytes>> {
+    // NOTE: URLs for the client are built from the host it reached, which may differ in a container
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .map_or_else(|| addr.to_string(), String::from);
+    let path = req.uri().path().to_string();
+
+    let res = match (req.method().clone(), path.as_str()) {
+        (_, "/link") => socket::upgrade(&mut req, &state.events),
+        (Method::GET, "/_fake/messages") => {
+            let workspace = state.workspace.lock().unwrap();
+            Ok(to_response(
+                StatusCode::OK,
+                &json!({ "messages": workspace.messages() }),
+            ))
+        }
+        (Method::POST, "/_fake/click") => click(req, state).await,
+        (_, path) if path.starts_with("/api/") => {
+            let method = path.trim_start_matches("/api/").to_string();
+            call(req, state, &method, &host).await
+        }
+        _ => Ok(to_response(
+            StatusCode::NOT_FOUND,
+            &json!({ "ok": false, "error": "not_found" }),
+        )),
+    };
+
+    res.unwrap_or_else(|e| {
+        warn!("{e:#}");
+        to_response(
+            StatusCode::BAD_REQUEST,
+            &json!({ "ok": false, "error": e.to_string() }),
+        )
+    })
+}
+
+async fn call(
+    req: Request<Incoming>,
+    state: &State,
+    method: &str,
+    host: &str,
+) -> Result<Response<Full<Bytes>>> {
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| !token.trim().is_empty());
+    let body = if authorized {
+        let params = read_params(req).await?;
+        debug!("Called {method}: {params:?}");
+        state.workspace.lock().unwrap().call(method, &params, host)
+    } else {
+        json!({ "ok": false, "error": "not_authed" })
+    };
+
+    let mut res = to_response(StatusCode::OK, &body);
+    res.headers_mut()
+        .insert("x-oauth-scopes", header::HeaderValue::from_static(SCOPES));
+    Ok(res)
+}
+
+async fn click(req: Request<Incoming>, state: &State) -> Result<Response<Full<Bytes>>> {
+    let body = req.into_body().collect().await?.to_bytes();
+    let click: Click = serde_json::from_slice(&body).with_context(
+        || "Invalid click. Expected `{\"button\": \"approve\", \"user\": \"U0123\"}`",
+    )?;
+    let payload = state.workspace.lock().unwrap().click(&click)?;
+
+    let envelope_id = state.envelopes.fetch_add(1, Ordering::Relaxed);
+    let envelope = json!({
+        "envelope_id": format!("fake-envelope-{envelope_id}"),
+        "type": "interactive",
+        "accepts_response_payload": false,
+        "payload": payload,
+    });
+    if state.events.send(envelope.to_string()).is_err() {
+        return Ok(to_response(
+            StatusCode::CONFLICT,
+            &json!({ "ok": false, "error": "No app is connected over socket mode" }),
+        ));
+    }
+
+    Ok(to_response(
+        StatusCode::OK,
+        &json!({
+            "ok": true,
+            "channel": payload["channel"]["id"],
+            "ts": payload["message"]["ts"],
+        }),
+    ))
+}
+
+// Query string, and the body in JSON or a form
+async fn read_params(req: Request<Incoming>) -> Result<Params> {
+    let mut params = Params::new();
+    if let Some(query) = req.uri().query() {
+        for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+            params.insert(name.into(), value.into_owned().into());
+        }
+    }
+    let is_json = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+
+    let body = req.into_body().collect().await?.to_bytes();
+    if body.is_empty() {
+        return Ok(params);
+    }
+    if is_json {
+        let json: Params =
+            serde_json::from_slice(&body).with_context(|| "Failed to parse JSON body")?;
+        params.extend(json);
+    } else {
+        for (name, value) in form_urlencoded::parse(&body) {
+            params.insert(name.into(), value.into_owned().into());
+        }
+    }
+
+    Ok(params)
+}
+
+fn to_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let mut res = Response::new(Full::new(Bytes::from(body.to_string())));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::comma("S0123: U0123, U0456", ("S0123", vec!["U0123", "U0456"]))]
+    #[case::space("S0123:U0123 U0456", ("S0123", vec!["U0123", "U0456"]))]
+    #[case::empty("S0123:", ("S0123", vec![]))]
+    fn should_parse_usergroup(#[case] line: &str, #[case] expected: (&str, Vec<&str>)) {
+        let (id, users) = parse_usergroup(line).unwrap();
+
+        assert_eq!(id, expected.0);
+        assert_eq!(users, expected.1);
+    }
+
+    #[test]
+    fn should_not_parse_usergroup_without_colon() {
+        assert!(parse_usergroup("S0123 U0123").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result, bail};
+use futures_util::{SinkExt, StreamExt};
+use http::{Request, Response, StatusCode, header};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper_util::rt::TokioIo;
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tracing::{debug, info, warn};
+
+use super::workspace::APP_ID;
+
+// Accepts the websocket of Socket Mode, which gets the envelopes sent to `events`
+pub(super) fn upgrade(
+    req: &mut Request<Incoming>,
+    events: &broadcast::Sender<String>,
+) -> Result<Response<Full<Bytes>>> {
+    let is_websocket = req
+        .headers()
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    let key = req.headers().get(header::SEC_WEBSOCKET_KEY);
+    let (true, Some(key)) = (is_websocket, key) else {
+        bail!("Socket mode needs a websocket");
+    };
+    let accept = derive_accept_key(key.as_bytes());
+
+    // NOTE: Subscribe before the handshake so that no click right after hello is missed
+    let receiver = events.subscribe();
+    let upgrade = hyper::upgrade::on(req);
+    tokio::spawn(async move {
+        match upgrade.await {
+            Ok(upgraded) => {
+                let socket =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+                if let Err(e) = serve(socket, receiver).await {
+                    warn!("{:?}", e);
+                }
+            }
+            Err(e) => warn!("Failed to upgrade to websocket: {e}"),
+        }
+    });
+
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept)
+        .body(Full::default())
+        .with_context(|| "Failed to build websocket response")
+}
+
+async fn serve<S>(
+    mut socket: WebSocketStream<S>,
+    mut events: broadcast::Receiver<String>,
+) -> Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let hello = json!({
+        "type": "hello",
+        "connection_info": { "app_id": APP_ID },
+    });
+    socket.send(Message::text(hello.to_string())).await?;
+    info!("App connected over socket mode");
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => socket.send(Message::text(event)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dropped {skipped} event(s) for a slow app");
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            message = socket.next() => match message {
+                Some(Ok(Message::Text(text))) => debug!("Acknowledged: {}", text.as_str()),
+                Some(Ok(Message::Close(_))) | None => {
+                    info!("App disconnected from socket mode");
+                    return Ok(());
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
@@ -0,0 +1,530 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use tracing::info;
+
+pub(super) const TEAM_ID: &str = "T0FAKE";
+pub(super) const APP_ID: &str = "A0FAKE";
+const BOT_USER_ID: &str = "U0FAKEBOT";
+const BOT_ID: &str = "B0FAKE";
+
+// Fields of a message which are replaced on `chat.update`
+const CONTENT_FIELDS: &[&str] = &["text", "blocks", "attachments", "metadata"];
+
+pub(super) type Params = Map<String, Value>;
+
+// Click on a button of a posted message, sent to the control API
+#[derive(Deserialize, PartialEq, Debug)]
+pub(super) struct Click {
+    // `action_id` or the text of the button, ignoring case
+    pub button: String,
+    pub user: String,
+    pub channel: Option<String>,
+    // The latest message with the button when not set
+    pub ts: Option<String>,
+}
+
+// What the fake Slack remembers until it exits
+pub(super) struct Workspace {
+    // Oldest first
+    messages: Vec<Value>,
+    usergroups: Vec<(String, Vec<String>)>,
+    last_ts: u128,
+}
+
+impl Workspace {
+    pub(super) fn new(usergroups: Vec<(String, Vec<String>)>) -> Self {
+        Self {
+            messages: Vec::new(),
+            usergroups,
+            last_ts: 0,
+        }
+    }
+
+    pub(super) fn messages(&self) -> &[Value] {
+        &self.messages
+    }
+
+    // Response of the Web API method
+    // `host` is where the client reached, to build URLs which it can reach too
+    pub(super) fn call(&mut self, method: &str, params: &Params, host: &str) -> Value {
+        match method {
+            "auth.test" => ok(json!({
+                "url": format!("http://{host}/"),
+                "team": "Fake Slack",
+                "user": "fake-bot",
+                "team_id": TEAM_ID,
+                "user_id": BOT_USER_ID,
+                "bot_id": BOT_ID,
+            })),
+            "apps.connections.open" => ok(json!({ "url": format!("ws://{host}/link") })),
+            "chat.postMessage" => self.post_message(params),
+            "chat.update" => self.update_message(params),
+            "chat.getPermalink" => self.get_permalink(params, host),
+            "conversations.history" => self.history(params),
+            "conversations.info" | "conversations.join" => match param(params, "channel") {
+                Some(channel) => ok(json!({ "channel": channel_info(&channel) })),
+                None => error("channel_not_found"),
+            },
+            "conversations.open" => match users(params).first() {
+                // NOTE: A direct message is a channel like any other here
+                Some(user) => ok(json!({ "channel": { "id": format!("D{user}") } })),
+                None => error("users_list_not_supplied"),
+            },
+            "users.info" => match param(params, "user") {
+                Some(user) => ok(json!({
+                    "user": {
+                        "id": user,
+                        "team_id": TEAM_ID,
+                        "name": user.to_lowercase(),
+                        "deleted": false,
+                        "is_bot": false,
+                    }
+                })),
+                None => error("user_not_found"),
+            },
+            "usergroups.list" => ok(json!({
+                "usergroups": self
+                    .usergroups
+                    .iter()
+                    .map(|(id, users)| usergroup(id, users))
+                    .collect::<Vec<Value>>()
+            })),
+            "usergroups.users.list" => {
+                let usergroup = param(params, "usergroup");
+                match self
+                    .usergroups
+                    .iter()
+                    .find(|(id, _)| Some(id) == usergroup.as_ref())
+                {
+                    Some((_, users)) => ok(json!({ "users": users })),
+                    None => error("no_such_subteam"),
+                }
+            }
+            _ => error("unknown_method"),
+        }
+    }
+
+    // Payload of `block_actions` as Slack sends it over Socket Mode
+    pub(super) fn click(&self, click: &Click) -> Result<Value> {
+        let found = self.messages.iter().rev().find_map(|message| {
+            if click.channel.is_some() && message["channel"].as_str() != click.channel.as_deref() {
+                return None;
+            }
+            if click.ts.is_some() && message["ts"].as_str() != click.ts.as_deref() {
+                return None;
+            }
+            find_button(message, &click.button).map(|button| (message, button))
+        });
+        let Some((message, (block_id, button))) = found else {
+            bail!("No message has the button: {}", click.button);
+        };
+        info!("Clicked {} by {}", click.button, click.user);
+
+        Ok(json!({
+            "type": "block_actions",
+            "team": { "id": TEAM_ID, "domain": "fake" },
+            "user": { "id": click.user, "team_id": TEAM_ID, "username": click.user.to_lowercase() },
+            "api_app_id": APP_ID,
+            "container": {
+                "type": "message",
+                "message_ts": message["ts"],
+                "channel_id": message["channel"],
+                "is_ephemeral": false,
+            },
+            "trigger_id": "fake-trigger",
+            "channel": { "id": message["channel"], "name": message["channel"] },
+            "message": message,
+            "actions": [{
+                "type": "button",
+                "action_id": button["action_id"],
+                "block_id": block_id,
+                "text": button["text"],
+                "value": button["value"],
+                "action_ts": message["ts"],
+            }],
+        }))
+    }
+
+    fn post_message(&mut self, params: &Params) -> Value {
+        let Some(channel) = param(params, "channel") else {
+            return error("channel_not_found");
+        };
+        let ts = self.next_ts();
+        let mut message = json!({
+            "type": "message",
+            "ts": ts,
+            "channel": channel,
+            "user": BOT_USER_ID,
+            "bot_id": BOT_ID,
+            "app_id": APP_ID,
+        });
+        if let Some(thread_ts) = param(params, "thread_ts") {
+            message["thread_ts"] = thread_ts.into();
+        }
+        set_content(&mut message, params);
+        info!("Posted message: channel: {channel}, ts: {ts}");
+        self.messages.push(message.clone());
+
+        ok(json!({ "channel": channel, "ts": ts, "message": message }))
+    }
+
+    fn update_message(&mut self, params: &Params) -> Value {
+        let edited_ts = self.next_ts();
+        let Some(message) = self.find_message_mut(params, "ts") else {
+            return error("message_not_found");
+        };
+        set_content(message, params);
+        message["edited"] = json!({ "user": BOT_USER_ID, "ts": edited_ts });
+        info!(
+            "Updated message: channel: {}, ts: {}",
+            message["channel"].as_str().unwrap_or_default(),
+            message["ts"].as_str().unwrap_or_default()
+        );
+
+        ok(json!({
+            "channel": message["channel"],
+            "ts": message["ts"],
+            "text": message["text"],
+            "message": message,
+        }))
+    }
+
+    fn get_permalink(&mut self, params: &Params, host: &str) -> Value {
+        let Some(message) = self.find_message_mut(params, "message_ts") else {
+            return error("message_not_found");
+        };
+        let channel = message["channel"].as_str().unwrap_or_default();
+        let ts = message["ts"].as_str().unwrap_or_default().replace('.', "");
+
+        ok(json!({
+            "channel": channel,
+            "permalink": format!("http://{host}/archives/{channel}/p{ts}"),
+        }))
+    }
+
+    // Messages in the channel except the replies, newest first
+    fn history(&self, params: &Params) -> Value {
+        let Some(channel) = param(params, "channel") else {
+            return error("channel_not_found");
+        };
+        let latest = param(params, "latest").map(|ts| ts_key(&ts));
+        let oldest = param(params, "oldest").map(|ts| ts_key(&ts));
+        let inclusive = param(params, "inclusive").is_some_and(|v| v == "true" || v == "1");
+        let limit = param(params, "limit")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100);
+
+        let messages: Vec<&Value> = self
+            .messages
+            .iter()
+            .rev()
+            .filter(|message| message["channel"].as_str() == Some(channel.as_str()))
+            .filter(|message| {
+                message
+                    .get("thread_ts")
+                    .is_none_or(|ts| *ts == message["ts"])
+            })
+            .filter(|message| {
+                let ts = ts_key(message["ts"].as_str().unwrap_or_default());
+                let before_latest =
+                    latest.is_none_or(|latest| ts < latest || (inclusive && ts == latest));
+                let after_oldest =
+                    oldest.is_none_or(|oldest| ts > oldest || (inclusive && ts == oldest));
+                before_latest && after_oldest
+            })
+            .take(limit)
+            .collect();
+
+        ok(json!({ "messages": messages, "has_more": false }))
+    }
+
+    fn find_message_mut(&mut self, params: &Params, ts_name: &str) -> Option<&mut Value> {
+        let channel = param(params, "channel")?;
+        let ts = param(params, ts_name)?;
+        self.messages.iter_mut().find(|message| {
+            message["channel"].as_str() == Some(channel.as_str())
+                && message["ts"].as_str() == Some(ts.as_str())
+        })
+    }
+
+    // Unique and increasing like the timestamps of Slack
+    fn next_ts(&mut self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+        self.last_ts = now.max(self.last_ts + 1);
+        format!(
+            "{}.{:06}",
+            self.last_ts / 1_000_000,
+            self.last_ts % 1_000_000
+        )
+    }
+}
+
+fn ok(mut value: Value) -> Value {
+    value["ok"] = true.into();
+    value
+}
+
+fn error(code: &str) -> Value {
+    json!({ "ok": false, "error": code })
+}
+
+// Parameters from the query are strings, and those from JSON are typed
+fn param(params: &Params, name: &str) -> Option<String> {
+    match params.get(name)? {
+        Value::String(v) => Some(v.clone()),
+        Value::Null => None,
+        v => Some(v.to_string()),
+    }
+}
+
+fn users(params: &Params) -> Vec<String> {
+    match params.get("users") {
+        Some(Value::Array(users)) => users
+            .iter()
+            .filter_map(|user| user.as_str().map(String::from))
+            .collect(),
+        Some(Value::String(users)) => users.split(',').map(|user| user.trim().into()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn set_content(message: &mut Value, params: &Params) {
+    for field in CONTENT_FIELDS {
+        let value = match params.get(*field) {
+            // NOTE: Form parameters have JSON in strings
+            Some(Value::String(v)) if *field != "text" => {
+                serde_json::from_str(v).unwrap_or_else(|_| v.clone().into())
+            }
+            Some(v) => v.clone(),
+            None => continue,
+        };
+        message[*field] = value;
+    }
+}
+
+// Block ID and the button
+fn find_button<'a>(message: &'a Value, button: &str) -> Option<(&'a Value, &'a Value)> {
+    let button = button.to_lowercase();
+    message["blocks"]
+        .as_array()?
+        .iter()
+        .filter(|block| block["type"] == "actions")
+        .find_map(|block| {
+            let found = block["elements"].as_array()?.iter().find(|element| {
+                element["type"] == "button"
+                    && (element["action_id"].as_str().map(str::to_lowercase)
+                        == Some(button.clone())
+                        || element["text"]["text"]
+                            .as_str()
+                            .is_some_and(|text| text.to_lowercase().contains(&button)))
+            })?;
+            Some((&block["block_id"], found))
+        })
+}
+
+fn channel_info(id: &str) -> Value {
+    json!({
+        "id": id,
+        "name": id.to_lowercase(),
+        "created": 0,
+        "is_channel": true,
+        "is_member": true,
+        "is_private": false,
+        "is_archived": false,
+    })
+}
+
+fn usergroup(id: &str, users: &[String]) -> Value {
+    json!({
+        "id": id,
+        "team_id": TEAM_ID,
+        "name": id,
+        "handle": id.to_lowercase(),
+        "is_external": false,
+        "date_create": 0,
+        "date_delete": 0,
+        "created_by": BOT_USER_ID,
+        "prefs": { "channels": [], "groups": [] },
+        "users": users,
+        "user_count": users.len(),
+    })
+}
+
+// Timestamps are compared as numbers, which strings may not be
+fn ts_key(ts: &str) -> (u64, u64) {
+    let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    (secs.parse().unwrap_or(0), micros.parse().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::de::DeserializeOwned;
+    use slack_morphism::prelude::*;
+
+    use super::*;
+
+    const HOST: &str = "localhost:3000";
+
+    fn params(value: Value) -> Params {
+        value.as_object().unwrap().clone()
+    }
+
+    // NOTE: The responses are read by slack-morphism, so they must be parsed by its types
+    fn parse<T: DeserializeOwned>(res: Value) -> T {
+        assert_eq!(res["ok"], true, "{res}");
+        serde_json::from_value(res).unwrap()
+    }
+
+    fn post_approval(workspace: &mut Workspace, channel: &str) -> String {
+        let res = workspace.call(
+            "chat.postMessage",
+            &params(json!({
+                "channel": channel,
+                "text": "Approval",
+                "blocks": [{
+                    "type": "actions",
+                    "block_id": "actions",
+                    "elements": [
+                        { "type": "button", "action_id": "slack-approval-approve", "text": { "type": "plain_text", "text": "✅Approve" }, "value": "approve" },
+                        { "type": "button", "action_id": "slack-approval-reject", "text": { "type": "plain_text", "text": "❌Reject" }, "value": "reject" },
+                    ],
+                }],
+            })),
+            HOST,
+        );
+        parse::<SlackApiChatPostMessageResponse>(res).ts.0
+    }
+
+    #[test]
+    fn should_update_posted_message() {
+        let mut workspace = Workspace::new(Vec::new());
+        let ts = post_approval(&mut workspace, "C1234567890");
+
+        let res = workspace.call(
+            "chat.update",
+            &params(json!({ "channel": "C1234567890", "ts": ts, "text": "Approved" })),
+            HOST,
+        );
+        parse::<SlackApiChatUpdateResponse>(res);
+
+        let res = workspace.call(
+            "conversations.history",
+            &params(json!({ "channel": "C1234567890", "latest": ts, "inclusive": "true", "limit": "1" })),
+            HOST,
+        );
+        let history: SlackApiConversationsHistoryResponse = parse(res);
+        assert_eq!(history.messages.len(), 1);
+        assert_eq!(history.messages[0].content.text, Some("Approved".into()));
+        assert!(history.messages[0].content.blocks.is_some());
+    }
+
+    #[test]
+    fn should_list_newest_first_without_replies() {
+        let mut workspace = Workspace::new(Vec::new());
+        let first = post_approval(&mut workspace, "C1234567890");
+        workspace.call(
+            "chat.postMessage",
+            &params(json!({ "channel": "C1234567890", "thread_ts": first, "text": "Reply" })),
+            HOST,
+        );
+        let second = post_approval(&mut workspace, "C1234567890");
+        post_approval(&mut workspace, "C0987654321");
+
+        let res = workspace.call(
+            "conversations.history",
+            &params(json!({ "channel": "C1234567890" })),
+            HOST,
+        );
+        let history: SlackApiConversationsHistoryResponse = parse(res);
+        let actual: Vec<String> = history
+            .messages
+            .into_iter()
+            .map(|m| m.origin.ts.0)
+            .collect();
+
+        assert_eq!(actual, vec![second, first]);
+    }
+
+    #[rstest]
+    #[case::action_id("slack-approval-reject", "slack-approval-reject")]
+    #[case::text("approve", "slack-approval-approve")]
+    fn should_click_button(#[case] button: &str, #[case] expected: &str) {
+        let mut workspace = Workspace::new(Vec::new());
+        let ts = post_approval(&mut workspace, "C1234567890");
+
+        let payload = workspace
+            .click(&Click {
+                button: button.into(),
+                user: "U1234567890".into(),
+                channel: None,
+                ts: None,
+            })
+            .unwrap();
+
+        let SlackInteractionEvent::BlockActions(event) = serde_json::from_value(payload).unwrap()
+        else {
+            panic!("Not block actions");
+        };
+        assert_eq!(event.user.unwrap().id.0, "U1234567890");
+        assert_eq!(event.channel.unwrap().id.0, "C1234567890");
+        assert_eq!(event.message.unwrap().origin.ts.0, ts);
+        assert_eq!(event.actions.unwrap()[0].action_id.0, expected);
+    }
+
+    #[test]
+    fn should_not_click_missing_button() {
+        let mut workspace = Workspace::new(Vec::new());
+        post_approval(&mut workspace, "C1234567890");
+
+        let actual = workspace.click(&Click {
+            button: "approve".into(),
+            user: "U1234567890".into(),
+            channel: Some("C0987654321".into()),
+            ts: None,
+        });
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn should_answer_check() {
+        let mut workspace =
+            Workspace::new(vec![("S1234567890".into(), vec!["U1234567890".into()])]);
+
+        parse::<SlackApiConversationsInfoResponse>(workspace.call(
+            "conversations.info",
+            &params(json!({ "channel": "C1234567890" })),
+            HOST,
+        ));
+        parse::<SlackApiUsersInfoResponse>(workspace.call(
+            "users.info",
+            &params(json!({ "user": "U1234567890" })),
+            HOST,
+        ));
+        let groups: SlackApiUserGroupsListResponse =
+            parse(workspace.call("usergroups.list", &Params::new(), HOST));
+        assert_eq!(groups.usergroups[0].id.0, "S1234567890");
+        let users: SlackApiUserGroupsUsersListResponse = parse(workspace.call(
+            "usergroups.users.list",
+            &params(json!({ "usergroup": "S1234567890" })),
+            HOST,
+        ));
+        assert_eq!(users.users, vec!["U1234567890".into()]);
+    }
+
+    #[test]
+    fn should_fail_on_unknown_method() {
+        let mut workspace = Workspace::new(Vec::new());
+
+        let actual = workspace.call("files.getUploadURLExternal", &Params::new(), HOST);
+
+        assert_eq!(actual, json!({ "ok": false, "error": "unknown_method" }));
+    }
+}
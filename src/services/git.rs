@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use git2::{Repository, Sort};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Commit {
+    pub sha: String,
+    // First line of the commit message
+    pub summary: String,
+    pub author: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CommitRange {
+    pub base: String,
+    // Newest first, up to the limit
+    pub commits: Vec<Commit>,
+    // Number of commits including the ones over the limit
+    pub total: usize,
+}
+
+// Lists the commits reachable from `head` but not from `base` like `git log base..head`
+pub fn list_commits(
+    repository_path: &Path,
+    head: &str,
+    base: &str,
+    limit: usize,
+) -> Result<CommitRange> {
+    // NOTE: The workspace is owned by the runner user while the container runs as root
+    unsafe { git2::opts::set_verify_owner_validation(false) }
+        .with_context(|| "Failed to disable owner validation of git repository")?;
+
+    let repository = Repository::open(repository_path).with_context(|| {
+        format!(
+            "Failed to open git repository. Have you checked out the repository? path: {}",
+            repository_path.display()
+        )
+    })?;
+    let head_oid = repository
+        .revparse_single(head)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .with_context(|| format!("Failed to find commit. head: {head}"))?;
+    let base_oid = repository
+        .revparse_single(base)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .with_context(|| {
+            format!(
+                "Failed to find base ref. Have you fetched it (e.g. `fetch-depth: 0`)? base: {base}"
+            )
+        })?;
+
+    let mut revwalk = repository.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk
+        .push(head_oid)
+        .with_context(|| format!("Failed to find commit. head: {head}"))?;
+    revwalk.hide(base_oid)?;
+
+    let mut commits = vec![];
+    let mut total = 0;
+    for oid in revwalk {
+        let oid =
+            oid.with_context(|| "Failed to walk commits. Have you fetched enough history?")?;
+        total += 1;
+        if commits.len() >= limit {
+            continue;
+        }
+
+        let commit = repository.find_commit(oid)?;
+        commits.push(Commit {
+            sha: oid.to_string(),
+            summary: commit.summary().unwrap_or_default().into(),
+            author: commit.author().name().unwrap_or_default().into(),
+        });
+    }
+
+    Ok(CommitRange {
+        base: base.into(),
+        commits,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Oid, Signature};
+
+    fn commit(repository: &Repository, message: &str, author: &str) -> Oid {
+        let signature = Signature::now(author, "octocat@example.com").unwrap();
+        let tree_id = repository.index().unwrap().write_tree().unwrap();
+        let tree = repository.find_tree(tree_id).unwrap();
+        let parent = repository
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap());
+        repository
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                parent.iter().collect::<Vec<_>>().as_slice(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn should_list_commits_since_base() {
+        let path = std::env::temp_dir().join("slack-approval-should-list-commits");
+        let _ = std::fs::remove_dir_all(&path);
+        let repository = Repository::init(&path).unwrap();
+
+        let base = commit(&repository, "Initial commit", "octocat");
+        repository
+            .tag_lightweight(
+                "v1.0.0",
+                &repository.find_object(base, None).unwrap(),
+                false,
+            )
+            .unwrap();
+        commit(&repository, "Add payments\n\nDetails", "monalisa");
+        commit(&repository, "Fix rounding", "hubot");
+        let head = commit(&repository, "Update docs", "octocat");
+
+        let actual = list_commits(&path, &head.to_string(), "v1.0.0", 2).unwrap();
+
+        assert_eq!(actual.base, "v1.0.0");
+        assert_eq!(actual.total, 3);
+        assert_eq!(
+            actual
+                .commits
+                .iter()
+                .map(|c| (c.summary.as_str(), c.author.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("Update docs", "octocat"), ("Fix rounding", "hubot")]
+        );
+        assert_eq!(actual.commits[0].sha, head.to_string());
+    }
+
+    #[test]
+    fn should_fail_when_base_is_not_found() {
+        let path = std::env::temp_dir().join("slack-approval-should-fail-when-base-is-not-found");
+        let _ = std::fs::remove_dir_all(&path);
+        let repository = Repository::init(&path).unwrap();
+        let head = commit(&repository, "Initial commit", "octocat");
+
+        let actual = list_commits(&path, &head.to_string(), "v9.9.9", 10).unwrap_err();
+
+        assert_eq!(
+            actual.to_string(),
+            "Failed to find base ref. Have you fetched it (e.g. `fetch-depth: 0`)? base: v9.9.9"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use anyhow::{Context, Result, bail};
+
+const STATE_PREFIX: &str = "STATE_";
+
+// Sets the outputs of the step which later steps read with `steps.<id>.outputs.<name>`
+// https://docs.github.com/en/actions/reference/workflows-and-actions/workflow-commands#setting-an-output-parameter
+pub fn set_outputs(outputs: &[(&str, String)]) -> Result<()> {
+    let Ok(path) = std::env::var("GITHUB_OUTPUT") else {
+        // NOTE: Outside GitHub Actions, scripts read the outputs from stdout
+        for (name, value) in outputs {
+            println!("{name}={value}");
+        }
+        return Ok(());
+    };
+
+    write_values(&path, outputs)
+}
+
+// Saves state which the post step of this action reads with `get_state`
+// https://docs.github.com/en/actions/reference/workflows-and-actions/workflow-commands#sending-values-to-the-pre-and-post-actions
+pub fn save_state(state: &[(&str, String)]) -> Result<()> {
+    // NOTE: There is no post step outside GitHub Actions
+    let Ok(path) = std::env::var("GITHUB_STATE") else {
+        return Ok(());
+    };
+
+    write_values(&path, state)
+}
+
+pub fn get_state(name: &str) -> Option<String> {
+    std::env::var(format!("{STATE_PREFIX}{name}"))
+        .ok()
+        .filter(|v| !v.is_empty())
+}
+
+fn write_values(path: &str, values: &[(&str, String)]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open file: {path}"))?;
+
+    for (name, value) in values {
+        // NOTE: Multiline values need a delimiter, but none of the values has a line break
+        if value.contains('\n') {
+            bail!("Value must be a single line: {name}");
+        }
+        writeln!(file, "{name}={value}").with_context(|| format!("Failed to write: {name}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_values() {
+        let path = std::env::temp_dir().join("slack-approval-should-write-values");
+        std::fs::write(&path, "existing=value\n").unwrap();
+        let path: String = path.to_string_lossy().into();
+
+        write_values(
+            &path,
+            &[
+                ("channel-id", "C1234567890".into()),
+                ("ts", "1700000000.000100".into()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "existing=value\nchannel-id=C1234567890\nts=1700000000.000100\n"
+        );
+    }
+
+    #[test]
+    fn should_get_state() {
+        unsafe {
+            std::env::set_var("STATE_file_commands_test", "1700000000");
+            std::env::set_var("STATE_file_commands_test_empty", "");
+        }
+
+        assert_eq!(get_state("file_commands_test"), Some("1700000000".into()));
+        assert_eq!(get_state("file_commands_test_empty"), None);
+        assert_eq!(get_state("file_commands_test_unset"), None);
+    }
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use slack_morphism::SlackApiTokenValue;
+
+use crate::services::config::Config;
+use crate::services::network::{NetworkOptions, read_network_options};
+
+// Inputs of the post step which reports the result of the job
+#[derive(PartialEq, Debug)]
+pub struct FinalizeInputs {
+    pub bot_token: SlackApiTokenValue,
+    // Token to read the steps of the job. The result is unknown without it
+    pub github_token: Option<String>,
+    pub post_job_status: bool,
+    pub network: NetworkOptions,
+}
+
+pub fn read_finalize_inputs(config: &Config) -> Result<FinalizeInputs> {
+    Ok(FinalizeInputs {
+        bot_token: config.get_required("bot-token")?.into(),
+        github_token: config.get_optional("github-token")?,
+        post_job_status: config.get_bool("post-job-status")?,
+        network: read_network_options(config)?,
+    })
+}
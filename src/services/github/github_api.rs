@@ -0,0 +1,168 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use chrono::DateTime;
+use serde::Deserialize;
+
+use super::github_info::GitHubInfo;
+use crate::services::network::{self, NetworkOptions};
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+// Result of the job like `job.status`, which is not available to Docker actions
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JobConclusion {
+    Success,
+    Failure,
+    Cancelled,
+}
+
+// The running job as the API sees it
+#[derive(Debug, PartialEq)]
+pub struct JobResult {
+    pub conclusion: JobConclusion,
+    // None when the API has no valid start time
+    pub started_at: Option<SystemTime>,
+}
+
+#[derive(Deserialize)]
+struct JobsResponse {
+    jobs: Vec<Job>,
+}
+
+#[derive(Deserialize)]
+struct Job {
+    status: String,
+    runner_name: Option<String>,
+    #[serde(default)]
+    started_at: Option<String>,
+    #[serde(default)]
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+struct Step {
+    conclusion: Option<String>,
+}
+
+// Concludes the running job from its steps
+// https://docs.github.com/en/rest/actions/workflow-jobs#list-jobs-for-a-workflow-run-attempt
+pub async fn fetch_job_result(
+    github_info: &GitHubInfo,
+    token: &str,
+    network: &NetworkOptions,
+) -> Result<JobResult> {
+    let url = format!(
+        "{}/repos/{}/actions/runs/{}/attempts/{}/jobs?filter=latest&per_page=100",
+        github_info
+            .github_api_url
+            .as_deref()
+            .unwrap_or(DEFAULT_API_URL),
+        github_info.github_repository,
+        github_info.github_run_id,
+        github_info.github_run_attempt
+    );
+    let res: JobsResponse = network::http_client(network)?
+        .get(&url)
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "slack-approval")
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .with_context(|| {
+            format!("Failed to fetch jobs. Has the token `actions: read` permission? url: {url}")
+        })?
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse jobs. url: {url}"))?;
+
+    let Some(job) = find_running_job(res.jobs, github_info.runner_name.as_deref()) else {
+        bail!("Running job is not found. url: {url}");
+    };
+
+    Ok(JobResult {
+        conclusion: conclude(&job.steps),
+        started_at: job.started_at.as_deref().and_then(parse_timestamp),
+    })
+}
+
+// Timestamps are in ISO 8601 like `2020-01-20T17:42:40Z`
+fn parse_timestamp(v: &str) -> Option<SystemTime> {
+    let secs = DateTime::parse_from_rfc3339(v).ok()?.timestamp();
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+// NOTE: Jobs have no ID in the environment, but the runner runs only one job at once
+fn find_running_job(jobs: Vec<Job>, runner_name: Option<&str>) -> Option<Job> {
+    jobs.into_iter().find(|job| {
+        job.status == "in_progress"
+            && (runner_name.is_none() || job.runner_name.as_deref() == runner_name)
+    })
+}
+
+fn conclude(steps: &[Step]) -> JobConclusion {
+    let conclusions: Vec<&str> = steps
+        .iter()
+        .filter_map(|step| step.conclusion.as_deref())
+        .collect();
+    if conclusions.contains(&"cancelled") {
+        JobConclusion::Cancelled
+    } else if conclusions.contains(&"failure") {
+        JobConclusion::Failure
+    } else {
+        JobConclusion::Success
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(vec![Some("success"), Some("skipped"), None], JobConclusion::Success)]
+    #[case(vec![Some("success"), Some("failure"), Some("skipped")], JobConclusion::Failure)]
+    #[case(vec![Some("success"), Some("cancelled"), Some("failure")], JobConclusion::Cancelled)]
+    fn test_conclude(#[case] conclusions: Vec<Option<&str>>, #[case] expected: JobConclusion) {
+        let steps: Vec<Step> = conclusions
+            .into_iter()
+            .map(|conclusion| Step {
+                conclusion: conclusion.map(|c| c.into()),
+            })
+            .collect();
+        assert_eq!(conclude(&steps), expected);
+    }
+
+    #[test]
+    fn should_find_running_job_on_this_runner() {
+        let res: JobsResponse = serde_json::from_str(
+            r#"{"jobs": [
+                {"status": "completed", "runner_name": "GitHub Actions 1", "steps": []},
+                {"status": "in_progress", "runner_name": "GitHub Actions 2", "steps": []},
+                {"status": "in_progress", "runner_name": "GitHub Actions 3", "started_at": "2020-01-20T17:42:40Z", "steps": [{"conclusion": "failure"}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let actual = find_running_job(res.jobs, Some("GitHub Actions 3")).unwrap();
+
+        assert_eq!(actual.runner_name.as_deref(), Some("GitHub Actions 3"));
+        assert_eq!(conclude(&actual.steps), JobConclusion::Failure);
+        assert_eq!(
+            actual.started_at.as_deref().and_then(parse_timestamp),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1579542160))
+        );
+    }
+
+    #[rstest]
+    #[case("2020-01-20T17:42:40Z", Some(1579542160))]
+    #[case("2020-01-20T17:42:40+09:00", Some(1579509760))]
+    #[case("", None)]
+    fn test_parse_timestamp(#[case] v: &str, #[case] expected: Option<u64>) {
+        assert_eq!(
+            parse_timestamp(v),
+            expected.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+        );
+    }
+}
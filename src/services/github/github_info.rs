@@ -1,14 +1,29 @@
+use std::fs::File;
+use std::io::BufReader;
+
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde_json::Value;
+
+use crate::services::ci::{CiInfo, Labels, optional_env};
 
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct GitHubInfo {
     pub github_server_url: String,
     pub github_repository: String,
     pub github_run_id: String,
+    pub github_run_number: String,
     pub github_workflow: String,
     pub runner_os: String,
     pub github_actor: String,
+    pub github_event_path: Option<String>,
+    pub github_sha: String,
+    pub github_ref_name: String,
+    pub github_event_name: String,
+    pub github_job: String,
+    pub github_run_attempt: String,
+    pub github_workspace: Option<String>,
+    pub github_api_url: Option<String>,
+    pub runner_name: Option<String>,
 }
 
 impl GitHubInfo {
@@ -19,14 +34,138 @@ impl GitHubInfo {
         )
     }
 
-    pub fn repository_url(&self) -> String {
-        format!("{}/{}", self.github_server_url, self.github_repository)
+    // Payload of the webhook event that triggered the workflow
+    pub fn read_event(&self) -> Result<Option<Value>> {
+        let Some(path) = &self.github_event_path else {
+            return Ok(None);
+        };
+
+        let file =
+            File::open(path).with_context(|| format!("Failed to open event payload: {path}"))?;
+        let event = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to parse event payload: {path}"))?;
+
+        Ok(Some(event))
     }
 }
 
-pub fn read_github_info() -> Result<GitHubInfo> {
-    envy::from_env::<GitHubInfo>()
-        .with_context(|| "Failed to read GitHub info from environment variables")
+impl CiInfo for GitHubInfo {
+    fn labels(&self) -> Labels {
+        Labels {
+            run: "Action",
+            workflow: "Workflow",
+        }
+    }
+
+    fn actor(&self) -> &str {
+        &self.github_actor
+    }
+
+    fn repository(&self) -> &str {
+        &self.github_repository
+    }
+
+    fn repository_url(&self) -> Option<String> {
+        Some(format!(
+            "{}/{}",
+            self.github_server_url, self.github_repository
+        ))
+    }
+
+    fn run_id(&self) -> &str {
+        &self.github_run_id
+    }
+
+    fn run_attempt(&self) -> Option<&str> {
+        Some(&self.github_run_attempt)
+    }
+
+    fn run_number(&self) -> &str {
+        &self.github_run_number
+    }
+
+    fn run_url(&self) -> Option<String> {
+        Some(self.action_url())
+    }
+
+    fn workflow(&self) -> &str {
+        &self.github_workflow
+    }
+
+    fn job(&self) -> &str {
+        &self.github_job
+    }
+
+    fn runner(&self) -> &str {
+        &self.runner_os
+    }
+
+    fn sha(&self) -> &str {
+        &self.github_sha
+    }
+
+    fn ref_name(&self) -> &str {
+        &self.github_ref_name
+    }
+
+    fn workspace(&self) -> Option<&str> {
+        self.github_workspace.as_deref()
+    }
+
+    fn github(&self) -> Option<&GitHubInfo> {
+        Some(self)
+    }
+}
+
+// https://docs.github.com/en/actions/reference/workflows-and-actions/variables#default-environment-variables
+pub fn read_github_info() -> GitHubInfo {
+    GitHubInfo {
+        github_server_url: optional_env("GITHUB_SERVER_URL")
+            .unwrap_or_else(|| "https://github.com".into()),
+        github_repository: env("GITHUB_REPOSITORY"),
+        github_run_id: env("GITHUB_RUN_ID"),
+        github_run_number: env("GITHUB_RUN_NUMBER"),
+        github_workflow: env("GITHUB_WORKFLOW"),
+        runner_os: env("RUNNER_OS"),
+        github_actor: env("GITHUB_ACTOR"),
+        github_event_path: optional_env("GITHUB_EVENT_PATH"),
+        github_sha: env("GITHUB_SHA"),
+        github_ref_name: env("GITHUB_REF_NAME"),
+        github_event_name: env("GITHUB_EVENT_NAME"),
+        github_job: env("GITHUB_JOB"),
+        github_run_attempt: optional_env("GITHUB_RUN_ATTEMPT").unwrap_or_else(|| "1".into()),
+        github_workspace: optional_env("GITHUB_WORKSPACE"),
+        github_api_url: optional_env("GITHUB_API_URL"),
+        runner_name: optional_env("RUNNER_NAME"),
+    }
+}
+
+fn env(name: &str) -> String {
+    optional_env(name).unwrap_or_default()
+}
+
+#[cfg(test)]
+impl GitHubInfo {
+    pub fn for_test() -> Self {
+        Self {
+            github_server_url: "https://github.com".into(),
+            github_repository: "octocat/Hello-World".into(),
+            github_run_id: "42".into(),
+            github_run_number: "7".into(),
+            github_workflow: "Deploy".into(),
+            runner_os: "Linux".into(),
+            github_actor: "octocat".into(),
+            github_event_path: None,
+            github_sha: "ffac537e6cbbf934b08745a378932722df287a53".into(),
+            github_ref_name: "main".into(),
+            github_event_name: "push".into(),
+            github_job: "approval".into(),
+            github_run_attempt: "1".into(),
+            github_workspace: None,
+            github_api_url: None,
+            runner_name: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -39,28 +178,72 @@ mod tests {
             std::env::set_var("GITHUB_SERVER_URL", "https://github.com");
             std::env::set_var("GITHUB_REPOSITORY", "octocat/Hello-World");
             std::env::set_var("GITHUB_RUN_ID", "42");
+            std::env::set_var("GITHUB_RUN_NUMBER", "7");
             std::env::set_var("GITHUB_WORKFLOW", "Hello-World-Workflow");
             std::env::set_var("RUNNER_OS", "Linux");
             std::env::set_var("GITHUB_ACTOR", "octocat");
+            std::env::remove_var("GITHUB_EVENT_PATH");
+            std::env::set_var("GITHUB_SHA", "ffac537e6cbbf934b08745a378932722df287a53");
+            std::env::set_var("GITHUB_REF_NAME", "main");
+            std::env::set_var("GITHUB_EVENT_NAME", "push");
+            std::env::set_var("GITHUB_JOB", "approval");
+            std::env::set_var("GITHUB_RUN_ATTEMPT", "1");
+            std::env::set_var("GITHUB_WORKSPACE", "/github/workspace");
+            std::env::set_var("GITHUB_API_URL", "https://api.github.com");
+            std::env::set_var("RUNNER_NAME", "GitHub Actions 2");
         }
 
         let expected = GitHubInfo {
             github_server_url: "https://github.com".into(),
             github_repository: "octocat/Hello-World".into(),
             github_run_id: "42".into(),
+            github_run_number: "7".into(),
             github_workflow: "Hello-World-Workflow".into(),
             runner_os: "Linux".into(),
             github_actor: "octocat".into(),
+            github_event_path: None,
+            github_sha: "ffac537e6cbbf934b08745a378932722df287a53".into(),
+            github_ref_name: "main".into(),
+            github_event_name: "push".into(),
+            github_job: "approval".into(),
+            github_run_attempt: "1".into(),
+            github_workspace: Some("/github/workspace".into()),
+            github_api_url: Some("https://api.github.com".into()),
+            runner_name: Some("GitHub Actions 2".into()),
         };
-        let actual = read_github_info().unwrap();
+        let actual = read_github_info();
         assert_eq!(actual, expected);
         assert_eq!(
             actual.action_url(),
             "https://github.com/octocat/Hello-World/actions/runs/42"
         );
         assert_eq!(
-            actual.repository_url(),
-            "https://github.com/octocat/Hello-World"
+            actual.repository_url().as_deref(),
+            Some("https://github.com/octocat/Hello-World")
+        );
+        assert_eq!(
+            actual.commit_url(actual.sha()).as_deref(),
+            Some(
+                "https://github.com/octocat/Hello-World/commit/ffac537e6cbbf934b08745a378932722df287a53"
+            )
+        );
+        assert_eq!(actual.short_sha(), "ffac537");
+    }
+
+    #[test]
+    fn should_read_event() {
+        let path = std::env::temp_dir().join("slack-approval-should-read-event.json");
+        std::fs::write(&path, r#"{"inputs": {"version": "1.42"}}"#).unwrap();
+
+        let github_info = GitHubInfo {
+            github_event_path: Some(path.to_string_lossy().into()),
+            ..GitHubInfo::for_test()
+        };
+
+        let actual = github_info.read_event().unwrap();
+        assert_eq!(
+            actual,
+            Some(serde_json::json!({"inputs": {"version": "1.42"}}))
         );
     }
 }
@@ -1,66 +1,511 @@
-use anyhow::Result;
-use slack_morphism::{SlackApiTokenValue, SlackChannelId, SlackUserGroupId, SlackUserId};
+use std::time::Duration;
 
-use super::input_utils::{get_list_input, get_required_input};
+use anyhow::{Context, Result, bail};
+
+use crate::services::config::Config;
+use crate::services::network::{NetworkOptions, read_network_options};
+
+// Bot Connector service for Teams in the public cloud
+const DEFAULT_TEAMS_SERVICE_URL: &str = "https://smba.trafficmanager.net/teams";
+
+// Where the approval is posted
+#[derive(PartialEq, Debug)]
+pub struct PostTo {
+    pub channel: bool,
+    // Each authorized user
+    pub direct_message: bool,
+}
+
+// Extra field shown in the message
+#[derive(PartialEq, Debug)]
+pub struct MessageField {
+    pub name: String,
+    pub value: String,
+}
+
+// What to do when the Terraform plan destroys resources
+#[derive(PartialEq, Debug)]
+pub enum DestroyPolicy {
+    Allow,
+    // Approvers confirm in a dialog
+    Confirm,
+    // Fails without posting the approval
+    Deny,
+}
+
+// How the message is posted
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Mode {
+    // Waits until someone approves or rejects
+    Approval,
+    // Posts without buttons and exits
+    Notify,
+}
+
+// Chat platform the approval is posted to
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Platform {
+    Slack,
+    Mattermost,
+    Teams,
+}
 
+#[derive(PartialEq, Debug)]
+pub struct MattermostInputs {
+    // Server like `https://mattermost.example.com`
+    pub url: String,
+    // Where Mattermost sends the clicks, which is put into the buttons. Not used in notify mode
+    pub callback_url: Option<String>,
+    // Address like `0.0.0.0:8080` to serve the clicks on. Not used in notify mode
+    pub listen_address: Option<String>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct TeamsInputs {
+    // Microsoft App ID of the bot, which is the audience of the clicks
+    pub app_id: String,
+    // Microsoft Entra tenant the app is registered in
+    pub tenant_id: String,
+    // Bot Connector service like `https://smba.trafficmanager.net/teams`, without a trailing slash
+    pub service_url: String,
+    // Address like `0.0.0.0:8080` to serve the messaging endpoint of the bot on. Not used in notify mode
+    pub listen_address: Option<String>,
+}
+
+// IDs and tokens are kept as strings, which each platform reads as its own
 #[derive(PartialEq, Debug)]
 pub struct GitHubInputs {
-    pub bot_token: SlackApiTokenValue,
-    pub app_token: SlackApiTokenValue,
-    pub channel_id: SlackChannelId,
-    pub mention_to_users: Vec<SlackUserId>,
-    pub mention_to_groups: Vec<SlackUserGroupId>,
-    pub authorized_users: Vec<SlackUserId>,
-    pub authorized_groups: Vec<SlackUserGroupId>,
+    pub platform: Platform,
+    pub mode: Mode,
+    pub bot_token: String,
+    // Not used in notify mode
+    pub app_token: Option<String>,
+    pub channel_ids: Vec<String>,
+    // Joins the public channels the bot is not in
+    pub join_channel: bool,
+    pub mention_to_users: Vec<String>,
+    pub mention_to_groups: Vec<String>,
+    pub authorized_users: Vec<String>,
+    pub authorized_groups: Vec<String>,
+    pub supersede_pending: bool,
+    pub timeout: Option<Duration>,
+    pub thread_ts: Option<String>,
+    pub thread_match: Option<String>,
+    pub reply_broadcast: bool,
+    pub post_to: PostTo,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub fields: Vec<MessageField>,
+    // GitHub-flavored Markdown converted into Slack mrkdwn
+    pub description_file: Option<String>,
+    pub template_file: Option<String>,
+    // Ref or tag of the last deployment to list the commits since
+    pub commits_base: Option<String>,
+    // Paths of files uploaded into the thread of the approval
+    pub attachments: Vec<String>,
+    // Output of `terraform show -json`
+    pub terraform_plan: Option<String>,
+    pub terraform_destroy: DestroyPolicy,
+    pub network: NetworkOptions,
+    // Only for Mattermost
+    pub mattermost: Option<MattermostInputs>,
+    // Only for Teams
+    pub teams: Option<TeamsInputs>,
 }
 
-pub fn read_github_inputs() -> Result<GitHubInputs> {
+pub fn read_github_inputs(config: &Config) -> Result<GitHubInputs> {
+    let platform = to_platform(config.get_optional("platform")?)?;
+    let mode = to_mode(config.get_optional("mode")?)?;
+    // NOTE: Mattermost and Teams send the clicks over HTTP to the runner instead of Socket Mode
+    let app_token = match (platform, mode) {
+        (Platform::Slack, Mode::Approval) => Some(config.get_required("app-token")?),
+        _ => config.get_optional("app-token")?,
+    };
+    let mattermost = match platform {
+        Platform::Slack | Platform::Teams => None,
+        Platform::Mattermost => Some(MattermostInputs {
+            url: config.get_required("mattermost-url")?,
+            callback_url: match mode {
+                Mode::Approval => Some(config.get_required("mattermost-callback-url")?),
+                Mode::Notify => config.get_optional("mattermost-callback-url")?,
+            },
+            listen_address: match mode {
+                Mode::Approval => Some(config.get_required("mattermost-listen-address")?),
+                Mode::Notify => config.get_optional("mattermost-listen-address")?,
+            },
+        }),
+    };
+    let teams = match platform {
+        Platform::Slack | Platform::Mattermost => None,
+        Platform::Teams => Some(TeamsInputs {
+            app_id: config.get_required("teams-app-id")?,
+            tenant_id: config.get_required("teams-tenant-id")?,
+            service_url: config.get_optional("teams-service-url")?.map_or_else(
+                || DEFAULT_TEAMS_SERVICE_URL.into(),
+                |url| url.trim_end_matches('/').into(),
+            ),
+            listen_address: match mode {
+                Mode::Approval => Some(config.get_required("teams-listen-address")?),
+                Mode::Notify => config.get_optional("teams-listen-address")?,
+            },
+        }),
+    };
+
     Ok(GitHubInputs {
-        bot_token: get_required_input("bot-token")?.into(),
-        app_token: get_required_input("app-token")?.into(),
-        channel_id: get_required_input("channel-id")?.into(),
-        mention_to_users: to_slack_user_id(get_list_input("mention-to-users")?),
-        mention_to_groups: to_slack_user_group_id(get_list_input("mention-to-groups")?),
-        authorized_users: to_slack_user_id(get_list_input("authorized-users")?),
-        authorized_groups: to_slack_user_group_id(get_list_input("authorized-groups")?),
+        platform,
+        mode,
+        bot_token: config.get_required("bot-token")?,
+        app_token,
+        channel_ids: config.get_required_list("channel-id")?,
+        join_channel: config.get_bool("join-channel")?,
+        mention_to_users: config.get_list("mention-to-users")?,
+        mention_to_groups: config.get_list("mention-to-groups")?,
+        authorized_users: config.get_list("authorized-users")?,
+        authorized_groups: config.get_list("authorized-groups")?,
+        supersede_pending: config.get_bool("supersede-pending")?,
+        timeout: to_timeout(config.get_optional("approval-timeout-minutes")?)?,
+        thread_ts: config.get_optional("thread-ts")?,
+        thread_match: config.get_optional("thread-match")?,
+        reply_broadcast: config.get_bool("reply-broadcast")?,
+        post_to: to_post_to(config.get_list("post-to")?)?,
+        title: config.get_optional("title")?,
+        description: config.get_optional("description")?,
+        fields: to_message_fields(config.get_multiline("fields")?)?,
+        description_file: config.get_optional("description-file")?,
+        template_file: config.get_optional("template-file")?,
+        commits_base: config.get_optional("commits-base")?,
+        attachments: config.get_multiline("attachments")?,
+        terraform_plan: config.get_optional("terraform-plan")?,
+        terraform_destroy: to_destroy_policy(config.get_optional("terraform-destroy")?)?,
+        network: read_network_options(config)?,
+        mattermost,
+        teams,
     })
 }
 
-fn to_slack_user_id(v: Vec<String>) -> Vec<SlackUserId> {
-    v.into_iter().map(|v| v.into()).collect()
+// Each line is `Name: value`
+fn to_message_fields(v: Vec<String>) -> Result<Vec<MessageField>> {
+    v.into_iter()
+        .map(|line| match line.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => Ok(MessageField {
+                name: name.trim().into(),
+                value: value.trim().into(),
+            }),
+            _ => bail!("Input 'fields' must be `Name: value` for each line: {line}"),
+        })
+        .collect()
+}
+
+fn to_post_to(v: Vec<String>) -> Result<PostTo> {
+    // NOTE: Post to the channel when nothing is specified
+    if v.is_empty() {
+        return Ok(PostTo {
+            channel: true,
+            direct_message: false,
+        });
+    }
+
+    let mut post_to = PostTo {
+        channel: false,
+        direct_message: false,
+    };
+    for target in v {
+        match target.as_str() {
+            "channel" => post_to.channel = true,
+            "direct-message" => post_to.direct_message = true,
+            _ => bail!("Input 'post-to' must be `channel` or `direct-message`: {target}"),
+        }
+    }
+
+    Ok(post_to)
 }
 
-fn to_slack_user_group_id(v: Vec<String>) -> Vec<SlackUserGroupId> {
-    v.into_iter().map(|v| v.into()).collect()
+fn to_platform(v: Option<String>) -> Result<Platform> {
+    match v.as_deref() {
+        None | Some("slack") => Ok(Platform::Slack),
+        Some("mattermost") => Ok(Platform::Mattermost),
+        Some("teams") => Ok(Platform::Teams),
+        Some(v) => bail!("Input 'platform' must be `slack`, `mattermost` or `teams`: {v}"),
+    }
+}
+
+fn to_mode(v: Option<String>) -> Result<Mode> {
+    match v.as_deref() {
+        None | Some("approval") => Ok(Mode::Approval),
+        Some("notify") => Ok(Mode::Notify),
+        Some(v) => bail!("Input 'mode' must be `approval` or `notify`: {v}"),
+    }
+}
+
+fn to_destroy_policy(v: Option<String>) -> Result<DestroyPolicy> {
+    match v.as_deref() {
+        None | Some("allow") => Ok(DestroyPolicy::Allow),
+        Some("confirm") => Ok(DestroyPolicy::Confirm),
+        Some("deny") => Ok(DestroyPolicy::Deny),
+        Some(v) => bail!("Input 'terraform-destroy' must be `allow`, `confirm` or `deny`: {v}"),
+    }
+}
+
+fn to_timeout(v: Option<String>) -> Result<Option<Duration>> {
+    v.map(|v| {
+        let minutes = v
+            .parse::<u64>()
+            .with_context(|| format!("Input 'approval-timeout-minutes' must be a number: {v}"))?;
+        let Some(secs) = minutes.checked_mul(60) else {
+            bail!("Input 'approval-timeout-minutes' is too large: {v}");
+        };
+        Ok(Duration::from_secs(secs))
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+impl GitHubInputs {
+    // Slack approval in one channel, with `values` added or replacing the defaults
+    pub fn for_test(values: &[(&str, &str)]) -> Self {
+        let mut all = vec![
+            ("bot-token", "xoxb-bot-token"),
+            ("app-token", "xapp-app-token"),
+            ("channel-id", "C1234567890"),
+        ];
+        all.extend_from_slice(values);
+        read_github_inputs(&Config::from_values(&all)).unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     #[test]
     fn should_read_github_inputs() {
-        unsafe {
-            std::env::set_var("INPUT_BOT-TOKEN", "xoxb-bot-token");
-            std::env::set_var("INPUT_APP-TOKEN", "xapp-app-token");
-            std::env::set_var("INPUT_CHANNEL-ID", "C1234567890");
-            std::env::set_var("INPUT_MENTION-TO-USERS", "U000001, U000002");
-            std::env::set_var("INPUT_MENTION-TO-GROUPS", "G000001, G000002, G000003");
-            std::env::set_var("INPUT_AUTHORIZED-USERS", "U000010, U000011");
-            std::env::set_var("INPUT_AUTHORIZED-GROUPS", "G000031, G000032");
-        }
+        let config = Config::from_values(&[
+            ("bot-token", "xoxb-bot-token"),
+            ("app-token", "xapp-app-token"),
+            ("channel-id", "C1234567890, C0987654321"),
+            ("join-channel", "true"),
+            ("mention-to-users", "U000001, U000002"),
+            ("mention-to-groups", "G000001, G000002, G000003"),
+            ("authorized-users", "U000010, U000011"),
+            ("authorized-groups", "G000031, G000032"),
+            ("supersede-pending", "true"),
+            ("approval-timeout-minutes", "10"),
+            ("thread-ts", "1700000000.000100"),
+            ("thread-match", "Release v1.42"),
+            ("reply-broadcast", "true"),
+            ("post-to", "channel, direct-message"),
+            ("title", "Deploy ${{ env.SERVICE }}"),
+            ("description", "Deploys *main*"),
+            ("fields", "Region: eu-west-1\nURL: https://example.com\n"),
+            ("description-file", "release-notes.md"),
+            ("template-file", ".github/approval.yml"),
+            ("commits-base", "v1.41.0"),
+            ("attachments", "plan.txt\nreports/diff.html\n"),
+            ("terraform-plan", "plan.json"),
+            ("terraform-destroy", "confirm"),
+            ("ca-certificates", "corp-ca.pem"),
+            ("slack-api-url", "https://slack-gov.com/api/"),
+        ]);
 
-        let actual = read_github_inputs().unwrap();
+        let actual = read_github_inputs(&config).unwrap();
         let expected = GitHubInputs {
+            platform: Platform::Slack,
+            mode: Mode::Approval,
             bot_token: "xoxb-bot-token".into(),
-            app_token: "xapp-app-token".into(),
-            channel_id: "C1234567890".into(),
+            app_token: Some("xapp-app-token".into()),
+            channel_ids: vec!["C1234567890".into(), "C0987654321".into()],
+            join_channel: true,
             mention_to_users: vec!["U000001".into(), "U000002".into()],
             mention_to_groups: vec!["G000001".into(), "G000002".into(), "G000003".into()],
             authorized_users: vec!["U000010".into(), "U000011".into()],
             authorized_groups: vec!["G000031".into(), "G000032".into()],
+            supersede_pending: true,
+            timeout: Some(Duration::from_secs(600)),
+            thread_ts: Some("1700000000.000100".into()),
+            thread_match: Some("Release v1.42".into()),
+            reply_broadcast: true,
+            post_to: PostTo {
+                channel: true,
+                direct_message: true,
+            },
+            title: Some("Deploy ${{ env.SERVICE }}".into()),
+            description: Some("Deploys *main*".into()),
+            fields: vec![
+                MessageField {
+                    name: "Region".into(),
+                    value: "eu-west-1".into(),
+                },
+                MessageField {
+                    name: "URL".into(),
+                    value: "https://example.com".into(),
+                },
+            ],
+            description_file: Some("release-notes.md".into()),
+            template_file: Some(".github/approval.yml".into()),
+            commits_base: Some("v1.41.0".into()),
+            attachments: vec!["plan.txt".into(), "reports/diff.html".into()],
+            terraform_plan: Some("plan.json".into()),
+            terraform_destroy: DestroyPolicy::Confirm,
+            network: NetworkOptions {
+                ca_certificates: vec!["corp-ca.pem".into()],
+                slack_api_url: Some("https://slack-gov.com/api".into()),
+            },
+            mattermost: None,
+            teams: None,
         };
 
         assert_eq!(actual, expected);
     }
+
+    #[rstest]
+    #[case(vec![], Ok((true, false)))]
+    #[case(vec!["channel".into()], Ok((true, false)))]
+    #[case(vec!["direct-message".into()], Ok((false, true)))]
+    #[case(vec!["direct-message".into(), "channel".into()], Ok((true, true)))]
+    #[case(
+        vec!["email".into()],
+        Err("Input 'post-to' must be `channel` or `direct-message`: email".into())
+    )]
+    fn test_to_post_to(#[case] v: Vec<String>, #[case] expected: Result<(bool, bool), String>) {
+        let actual = to_post_to(v)
+            .map(|post_to| (post_to.channel, post_to.direct_message))
+            .map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Ok(Mode::Approval))]
+    #[case(Some("approval".into()), Ok(Mode::Approval))]
+    #[case(Some("notify".into()), Ok(Mode::Notify))]
+    #[case(
+        Some("approve".into()),
+        Err("Input 'mode' must be `approval` or `notify`: approve".into())
+    )]
+    fn test_to_mode(#[case] v: Option<String>, #[case] expected: Result<Mode, String>) {
+        let actual = to_mode(v).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Ok(None))]
+    #[case(Some("10".into()), Ok(Some(Duration::from_secs(600))))]
+    #[case(
+        Some("ten".into()),
+        Err("Input 'approval-timeout-minutes' must be a number: ten".into())
+    )]
+    #[case(
+        Some(u64::MAX.to_string()),
+        Err(format!("Input 'approval-timeout-minutes' is too large: {}", u64::MAX))
+    )]
+    fn test_to_timeout(
+        #[case] v: Option<String>,
+        #[case] expected: Result<Option<Duration>, String>,
+    ) {
+        let actual = to_timeout(v).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case(None, Ok(Platform::Slack))]
+    #[case(Some("mattermost".into()), Ok(Platform::Mattermost))]
+    #[case(Some("teams".into()), Ok(Platform::Teams))]
+    #[case(
+        Some("discord".into()),
+        Err("Input 'platform' must be `slack`, `mattermost` or `teams`: discord".into())
+    )]
+    fn test_to_platform(#[case] v: Option<String>, #[case] expected: Result<Platform, String>) {
+        let actual = to_platform(v).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_read_mattermost_inputs_without_app_token() {
+        let config = Config::from_values(&[
+            ("platform", "mattermost"),
+            ("bot-token", "mattermost-bot-token"),
+            ("channel-id", "4xp9fdt77pncbef59f4k1qe83o"),
+            ("mattermost-url", "https://mattermost.example.com"),
+            (
+                "mattermost-callback-url",
+                "https://runner.example.com/slack-approval",
+            ),
+            ("mattermost-listen-address", "127.0.0.1:8080"),
+        ]);
+
+        let actual = read_github_inputs(&config).unwrap();
+
+        assert_eq!(actual.app_token, None);
+        assert_eq!(
+            actual.mattermost,
+            Some(MattermostInputs {
+                url: "https://mattermost.example.com".into(),
+                callback_url: Some("https://runner.example.com/slack-approval".into()),
+                listen_address: Some("127.0.0.1:8080".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn should_require_callback_url_for_mattermost_approval() {
+        let config = Config::from_values(&[
+            ("platform", "mattermost"),
+            ("bot-token", "mattermost-bot-token"),
+            ("channel-id", "4xp9fdt77pncbef59f4k1qe83o"),
+            ("mattermost-url", "https://mattermost.example.com"),
+        ]);
+
+        assert!(read_github_inputs(&config).is_err());
+    }
+
+    #[test]
+    fn should_read_teams_inputs() {
+        let config = Config::from_values(&[
+            ("platform", "teams"),
+            ("bot-token", "teams-client-secret"),
+            ("channel-id", "19:4a1f2b3c@thread.tacv2"),
+            ("teams-app-id", "00000000-0000-0000-0000-000000000001"),
+            ("teams-tenant-id", "00000000-0000-0000-0000-000000000002"),
+            ("teams-listen-address", "127.0.0.1:3978"),
+        ]);
+
+        let actual = read_github_inputs(&config).unwrap();
+
+        assert_eq!(actual.app_token, None);
+        assert_eq!(
+            actual.teams,
+            Some(TeamsInputs {
+                app_id: "00000000-0000-0000-0000-000000000001".into(),
+                tenant_id: "00000000-0000-0000-0000-000000000002".into(),
+                service_url: "https://smba.trafficmanager.net/teams".into(),
+                listen_address: Some("127.0.0.1:3978".into()),
+            })
+        );
+    }
+
+    #[rstest]
+    #[case(None, Ok(DestroyPolicy::Allow))]
+    #[case(Some("allow".into()), Ok(DestroyPolicy::Allow))]
+    #[case(Some("confirm".into()), Ok(DestroyPolicy::Confirm))]
+    #[case(Some("deny".into()), Ok(DestroyPolicy::Deny))]
+    #[case(
+        Some("ask".into()),
+        Err("Input 'terraform-destroy' must be `allow`, `confirm` or `deny`: ask".into())
+    )]
+    fn test_to_destroy_policy(
+        #[case] v: Option<String>,
+        #[case] expected: Result<DestroyPolicy, String>,
+    ) {
+        let actual = to_destroy_policy(v).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_reject_field_without_name() {
+        let actual = to_message_fields(vec![": value".into()]).map_err(|e| e.to_string());
+        assert_eq!(
+            actual,
+            Err("Input 'fields' must be `Name: value` for each line: : value".into())
+        );
+    }
 }
@@ -1,3 +1,6 @@
+pub mod file_commands;
+pub mod finalize_inputs;
+pub mod github_api;
 pub mod github_info;
 pub mod github_inputs;
-mod input_utils;
+pub mod update_inputs;
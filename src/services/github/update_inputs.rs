@@ -0,0 +1,88 @@
+use anyhow::{Result, bail};
+use slack_morphism::{SlackApiTokenValue, SlackChannelId, SlackTs};
+
+use crate::services::config::Config;
+use crate::services::network::{NetworkOptions, read_network_options};
+
+// Where the status is appended
+#[derive(PartialEq, Debug)]
+pub enum AppendTo {
+    // Reply in the thread of the approval
+    Thread,
+    // Add a line at the bottom of the approval
+    Message,
+}
+
+// Inputs of the `update` command which appends status to a posted approval
+#[derive(PartialEq, Debug)]
+pub struct UpdateInputs {
+    pub bot_token: SlackApiTokenValue,
+    pub channel_id: SlackChannelId,
+    pub message_ts: SlackTs,
+    // Thread the approval is in, if any
+    pub thread_ts: Option<SlackTs>,
+    pub status: String,
+    pub append_to: AppendTo,
+    pub network: NetworkOptions,
+}
+
+pub fn read_update_inputs(config: &Config) -> Result<UpdateInputs> {
+    Ok(UpdateInputs {
+        bot_token: config.get_required("bot-token")?.into(),
+        channel_id: to_single_channel_id(config.get_required("channel-id")?)?,
+        message_ts: config.get_required("message-ts")?.into(),
+        thread_ts: config.get_optional("thread-ts")?.map(|v| v.into()),
+        status: config.get_required("status")?,
+        append_to: to_append_to(config.get_optional("append-to")?)?,
+        network: read_network_options(config)?,
+    })
+}
+
+// NOTE: The message ts is different in each channel
+fn to_single_channel_id(v: String) -> Result<SlackChannelId> {
+    if v.contains(',') {
+        bail!("Input 'channel-id' must be a single channel to update: {v}");
+    }
+
+    Ok(v.into())
+}
+
+fn to_append_to(v: Option<String>) -> Result<AppendTo> {
+    match v.as_deref() {
+        None | Some("thread") => Ok(AppendTo::Thread),
+        Some("message") => Ok(AppendTo::Message),
+        Some(v) => bail!("Input 'append-to' must be `thread` or `message`: {v}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(None, Ok(AppendTo::Thread))]
+    #[case(Some("thread".into()), Ok(AppendTo::Thread))]
+    #[case(Some("message".into()), Ok(AppendTo::Message))]
+    #[case(
+        Some("channel".into()),
+        Err("Input 'append-to' must be `thread` or `message`: channel".into())
+    )]
+    fn test_to_append_to(#[case] v: Option<String>, #[case] expected: Result<AppendTo, String>) {
+        let actual = to_append_to(v).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_reject_multiple_channels() {
+        let actual =
+            to_single_channel_id("C1234567890,C0987654321".into()).map_err(|e| e.to_string());
+        assert_eq!(
+            actual,
+            Err(
+                "Input 'channel-id' must be a single channel to update: C1234567890,C0987654321"
+                    .into()
+            )
+        );
+    }
+}
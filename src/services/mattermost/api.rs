@@ -0,0 +1,119 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::services::network::{self, NetworkOptions};
+
+// Members of a group which are read at once
+const MEMBERS_PER_PAGE: usize = 200;
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+pub(super) struct Post {
+    pub id: String,
+    pub channel_id: String,
+}
+
+#[derive(Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Members {
+    members: Vec<User>,
+}
+
+// Body of an error response
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+// REST API v4 of Mattermost with a bot token
+// https://api.mattermost.com/
+pub(super) struct MattermostApi {
+    client: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl MattermostApi {
+    pub(super) fn new(url: &str, token: &str, network: &NetworkOptions) -> Result<Self> {
+        Ok(Self {
+            client: network::http_client(network)?,
+            url: url.trim_end_matches('/').into(),
+            token: token.into(),
+        })
+    }
+
+    // `post` has `channel_id`, `message` and optionally `root_id` and `props`
+    pub(super) async fn create_post(&self, post: &Value) -> Result<Post> {
+        let req = self
+            .client
+            .post(format!("{}/api/v4/posts", self.url))
+            .json(post);
+        self.send(req, "create post").await
+    }
+
+    // Replaces only the fields in `patch`
+    pub(super) async fn patch_post(&self, post_id: &str, patch: &Value) -> Result<()> {
+        let req = self
+            .client
+            .put(format!("{}/api/v4/posts/{post_id}/patch", self.url))
+            .json(patch);
+        let _: Value = self.send(req, "patch post").await?;
+
+        Ok(())
+    }
+
+    pub(super) async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+        let mut user_ids = vec![];
+        for page in 0.. {
+            let req = self
+                .client
+                .get(format!("{}/api/v4/groups/{group_id}/members", self.url))
+                .query(&[("page", page), ("per_page", MEMBERS_PER_PAGE)]);
+            let res: Members = self
+                .send(req, "list group members")
+                .await
+                .with_context(|| format!("group_id: {group_id}"))?;
+            let count = res.members.len();
+            user_ids.extend(res.members.into_iter().map(|user| user.id));
+            if count < MEMBERS_PER_PAGE {
+                break;
+            }
+        }
+
+        Ok(user_ids)
+    }
+
+    pub(super) fn permalink(&self, post_id: &str) -> String {
+        format!("{}/_redirect/pl/{post_id}", self.url)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        req: reqwest::RequestBuilder,
+        what: &str,
+    ) -> Result<T> {
+        let res = req
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to {what}. url: {}", self.url))?;
+        let status = res.status();
+        if !status.is_success() {
+            // NOTE: Mattermost tells what went wrong like missing permissions in the body
+            let message = res
+                .json::<ApiError>()
+                .await
+                .map_or_else(|_| status.to_string(), |e| e.message);
+            bail!("Failed to {what}: {message}");
+        }
+
+        res.json()
+            .await
+            .with_context(|| format!("Failed to parse response to {what}"))
+    }
+}
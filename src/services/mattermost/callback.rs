@@ -0,0 +1,164 @@
+use anyhow::{Context, Result, bail};
+use http::{Method, Request, Response, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::services::chat::callback::Route;
+use crate::services::chat::{Decision, Interaction};
+
+// Request which Mattermost sends when a button is clicked
+// https://developers.mattermost.com/integrate/plugins/interactive-messages/
+#[derive(Deserialize)]
+struct ActionRequest {
+    user_id: String,
+    #[serde(default)]
+    user_name: Option<String>,
+    channel_id: String,
+    context: ActionContext,
+}
+
+#[derive(Deserialize)]
+struct ActionContext {
+    action: String,
+    secret: String,
+}
+
+// Reads the clicks which have the secret of this approval
+pub(super) struct ActionRoute {
+    pub(super) secret: String,
+}
+
+impl Route for ActionRoute {
+    async fn route(
+        &self,
+        req: Request<Incoming>,
+        sender: &mpsc::UnboundedSender<Interaction>,
+    ) -> Response<Full<Bytes>> {
+        route(req, &self.secret, sender).await
+    }
+}
+
+async fn route(
+    req: Request<Incoming>,
+    secret: &str,
+    sender: &mpsc::UnboundedSender<Interaction>,
+) -> Response<Full<Bytes>> {
+    if req.method() != Method::POST {
+        return to_response(StatusCode::NOT_FOUND, "Not found");
+    }
+
+    let interaction = match read_interaction(req, secret).await {
+        Ok(Some(interaction)) => interaction,
+        Ok(None) => return to_response(StatusCode::FORBIDDEN, "Secret does not match"),
+        Err(e) => {
+            warn!("{e:#}");
+            return to_response(StatusCode::BAD_REQUEST, &e.to_string());
+        }
+    };
+    if sender.send(interaction).is_err() {
+        return to_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Approval is not waiting for clicks anymore",
+        );
+    }
+
+    // NOTE: Mattermost expects JSON, which can update the post. The post is updated separately
+    let mut res = Response::new(Full::new(Bytes::from("{}")));
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+// None when the secret does not match
+async fn read_interaction(req: Request<Incoming>, secret: &str) -> Result<Option<Interaction>> {
+    let body = req.into_body().collect().await?.to_bytes();
+    let action: ActionRequest =
+        serde_json::from_slice(&body).with_context(|| "Invalid action request")?;
+    if action.context.secret != secret {
+        warn!("Click with a wrong secret is ignored: {}", action.user_id);
+        return Ok(None);
+    }
+    let decision = match action.context.action.as_str() {
+        "approve" => Decision::Approved,
+        "reject" => Decision::Rejected,
+        action => bail!("Unknown action: {action}"),
+    };
+
+    Ok(Some(Interaction {
+        mention: action
+            .user_name
+            .map_or_else(|| action.user_id.clone(), |name| format!("@{name}")),
+        user_id: action.user_id,
+        channel_id: action.channel_id,
+        decision,
+    }))
+}
+
+fn to_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    let body = json!({ "error": { "message": message } });
+    let mut res = Response::new(Full::new(Bytes::from(body.to_string())));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::chat::callback::Callback;
+
+    fn click(action: &str, secret: &str) -> serde_json::Value {
+        json!({
+            "user_id": "rq9fsbcbqbfcdpcrjz3qoqh6ga",
+            "user_name": "alice",
+            "channel_id": "4xp9fdt77pncbef59f4k1qe83o",
+            "post_id": "xj1aw6dkgtd5mpwjkgrf7wz5ha",
+            "context": { "action": action, "secret": secret },
+        })
+    }
+
+    #[tokio::test]
+    async fn should_receive_click_with_secret() {
+        let route = ActionRoute {
+            secret: "secret".into(),
+        };
+        let mut callback = Callback::listen("127.0.0.1:0", route).await.unwrap();
+        let url = format!("http://127.0.0.1:{}/hooks/approval", callback.addr().port());
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(&url)
+            .json(&click("approve", "wrong"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = client
+            .post(&url)
+            .json(&click("reject", "secret"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        assert_eq!(
+            callback.next().await.unwrap(),
+            Interaction {
+                user_id: "rq9fsbcbqbfcdpcrjz3qoqh6ga".into(),
+                mention: "@alice".into(),
+                channel_id: "4xp9fdt77pncbef59f4k1qe83o".into(),
+                decision: Decision::Rejected,
+            }
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::services::ci::CiInfo;
+use crate::services::github::github_inputs::GitHubInputs;
+use crate::services::template::TemplateContext;
+
+// Message attachment of the approval
+// https://developers.mattermost.com/integrate/reference/message-attachments/
+pub(super) fn build_attachment(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+) -> Result<Value> {
+    let title = github_inputs
+        .title
+        .as_deref()
+        .map(|title| context.render(title))
+        .transpose()
+        .with_context(|| "Failed to render title")?;
+
+    // NOTE: Mattermost renders Markdown, so the description file is shown as it is
+    let mut text = vec![];
+    if let Some(description) = &github_inputs.description {
+        text.push(
+            context
+                .render(description)
+                .with_context(|| "Failed to render description")?,
+        );
+    }
+    if let Some(path) = &github_inputs.description_file {
+        text.push(
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read description file: {path}"))?,
+        );
+    }
+
+    let mut attachment = json!({
+        // Shown in notifications
+        "fallback": title.clone().unwrap_or_else(|| "Approval".into()),
+        "text": text.join("\n\n"),
+        "fields": build_fields(github_inputs, ci_info, context)?,
+    });
+    if let Some(title) = title {
+        attachment["title"] = title.into();
+    }
+
+    Ok(attachment)
+}
+
+// Mattermost mentions by username, like `@alice` and `@release-managers`
+pub(super) fn build_header(github_inputs: &GitHubInputs) -> String {
+    github_inputs
+        .mention_to_users
+        .iter()
+        .map(String::as_str)
+        .chain(github_inputs.mention_to_groups.iter().map(String::as_str))
+        .map(|name| format!("@{}", name.trim_start_matches('@')))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Mattermost sends the click to `callback_url` with the context
+// NOTE: The secret tells the clicks from requests by anyone else who can reach the runner
+pub(super) fn build_actions(callback_url: &str, secret: &str) -> Value {
+    let action = |id: &str, name: &str, style: &str| {
+        json!({
+            "id": id,
+            "name": name,
+            "style": style,
+            "integration": {
+                "url": callback_url,
+                "context": { "action": id, "secret": secret },
+            },
+        })
+    };
+
+    json!([
+        action("approve", "✅Approve", "success"),
+        action("reject", "❌Reject", "danger"),
+    ])
+}
+
+// Removes the buttons so that no one can decide anymore and shows the result instead
+pub(super) fn replace_actions(attachment: &Value, text: &str) -> Value {
+    let mut attachment = attachment.clone();
+    if let Some(attachment) = attachment.as_object_mut() {
+        attachment.remove("actions");
+    }
+    let body = attachment["text"].as_str().unwrap_or_default();
+    attachment["text"] = if body.is_empty() {
+        text.into()
+    } else {
+        format!("{body}\n\n{text}").into()
+    };
+
+    attachment
+}
+
+fn build_fields(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+) -> Result<Vec<Value>> {
+    let field =
+        |title: &str, value: String| json!({ "title": title, "value": value, "short": true });
+    let labels = ci_info.labels();
+    let mut fields = vec![];
+    if !ci_info.actor().is_empty() {
+        fields.push(field("👤Actor", ci_info.actor().into()));
+    }
+    if let Some(url) = ci_info.repository_url() {
+        fields.push(field("📦Repository", url));
+    }
+    if let Some(url) = ci_info.run_url() {
+        fields.push(field(&format!("🚀{}", labels.run), url));
+    }
+    let workflow = [ci_info.workflow(), ci_info.job()]
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" / ");
+    if !workflow.is_empty() {
+        fields.push(field(&format!("🔄{}", labels.workflow), workflow));
+    }
+
+    for input in &github_inputs.fields {
+        let value = context
+            .render(&input.value)
+            .with_context(|| format!("Failed to render field: {}", input.name))?;
+        fields.push(field(&input.name, value));
+    }
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+
+    const VALUES: &[(&str, &str)] = &[
+        ("platform", "mattermost"),
+        ("bot-token", "mattermost-bot-token"),
+        ("channel-id", "4xp9fdt77pncbef59f4k1qe83o"),
+        ("mattermost-url", "https://mattermost.example.com"),
+        ("mattermost-callback-url", "http://runner.internal:8080/"),
+        ("mattermost-listen-address", "0.0.0.0:8080"),
+        ("mention-to-users", "alice, @bob"),
+        ("mention-to-groups", "release-managers"),
+        ("title", "Deploy run #${{ github.run_number }}"),
+        ("description", "By **${{ github.actor }}**"),
+        ("fields", "Target: ${{ github.repository }}"),
+    ];
+
+    #[test]
+    fn should_build_attachment() {
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual =
+            build_attachment(&GitHubInputs::for_test(VALUES), &github_info, &context).unwrap();
+
+        assert_eq!(actual["title"], "Deploy run #7");
+        assert_eq!(actual["fallback"], "Deploy run #7");
+        assert_eq!(actual["text"], "By **octocat**");
+        let fields = actual["fields"].as_array().unwrap();
+        assert_eq!(
+            fields.last().unwrap(),
+            &json!({ "title": "Target", "value": "octocat/Hello-World", "short": true })
+        );
+    }
+
+    #[test]
+    fn should_mention_by_username() {
+        assert_eq!(
+            build_header(&GitHubInputs::for_test(VALUES)),
+            "@alice @bob @release-managers"
+        );
+    }
+
+    #[test]
+    fn should_replace_actions_with_outcome() {
+        let attachment = json!({
+            "text": "Deploys main",
+            "actions": build_actions("http://runner.internal:8080/", "secret"),
+        });
+
+        let actual = replace_actions(&attachment, "Approved by @alice");
+
+        assert_eq!(
+            actual,
+            json!({ "text": "Deploys main\n\nApproved by @alice" })
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use tracing::info;
+
+use crate::services::chat::callback::Callback;
+use crate::services::chat::{self, Interaction, Outcome, Transport};
+use crate::services::ci::CiInfo;
+use crate::services::github::github_inputs::{GitHubInputs, Mode};
+use crate::services::template::TemplateContext;
+use crate::services::termination::Termination;
+use api::{MattermostApi, Post};
+use callback::ActionRoute;
+
+mod api;
+mod callback;
+mod content;
+
+pub async fn handle_mattermost_approval(
+    ci_info: &dyn CiInfo,
+    github_inputs: &GitHubInputs,
+    termination: Termination,
+) -> Result<()> {
+    let Some(mattermost) = &github_inputs.mattermost else {
+        bail!("Input 'mattermost-url' is required");
+    };
+    chat::warn_unsupported_inputs(github_inputs, "Mattermost");
+    chat::check_destroy_policy(github_inputs, "Mattermost")?;
+
+    let context = TemplateContext::new(ci_info)?
+        .with_value("approval.mentions", content::build_header(github_inputs));
+    let mut attachment = content::build_attachment(github_inputs, ci_info, &context)
+        .with_context(|| "Failed to build message")?;
+
+    let callback = match (
+        github_inputs.mode,
+        &mattermost.callback_url,
+        &mattermost.listen_address,
+    ) {
+        (Mode::Approval, Some(callback_url), Some(listen_address)) => {
+            let secret = chat::generate_secret()?;
+            attachment["actions"] = content::build_actions(callback_url, &secret);
+            let callback = Callback::listen(listen_address, ActionRoute { secret }).await?;
+            info!("Listening for clicks on {}", callback.addr());
+            Some(callback)
+        }
+        (Mode::Approval, None, _) => bail!("Input 'mattermost-callback-url' is required"),
+        (Mode::Approval, _, None) => bail!("Input 'mattermost-listen-address' is required"),
+        (Mode::Notify, _, _) => None,
+    };
+
+    let transport = MattermostTransport {
+        api: MattermostApi::new(
+            &mattermost.url,
+            &github_inputs.bot_token,
+            &github_inputs.network,
+        )?,
+        message: content::build_header(github_inputs),
+        attachment,
+        posts: vec![],
+        callback,
+    };
+    chat::run_approval(transport, github_inputs, termination, |transport| {
+        let Some(post) = transport.posts.first() else {
+            bail!("Approval was not posted to anywhere");
+        };
+        Ok(vec![
+            ("channel-id", post.channel_id.clone()),
+            // NOTE: Mattermost identifies a post by its ID instead of a timestamp
+            ("ts", post.id.clone()),
+            ("permalink", transport.api.permalink(&post.id)),
+        ])
+    })
+    .await
+}
+
+// Posts the approval as a message attachment and receives the clicks on the callback URL
+struct MattermostTransport {
+    api: MattermostApi,
+    message: String,
+    attachment: Value,
+    // Every copy is updated once someone decides
+    posts: Vec<Post>,
+    callback: Option<Callback>,
+}
+
+impl Transport for MattermostTransport {
+    async fn post(&mut self, channel_id: &str) -> Result<()> {
+        let post = self
+            .api
+            .create_post(&json!({
+                "channel_id": channel_id,
+                "message": self.message,
+                "props": { "attachments": [self.attachment] },
+            }))
+            .await
+            .with_context(|| format!("Failed to post approval. channel_id: {channel_id}"))?;
+        self.posts.push(post);
+
+        Ok(())
+    }
+
+    async fn update(&self, outcome: &Outcome) -> Result<()> {
+        let attachment = content::replace_actions(&self.attachment, &outcome.text());
+        let patch = json!({ "props": { "attachments": [attachment] } });
+        chat::update_each(self.posts.iter().map(|post| async {
+            self.api
+                .patch_post(&post.id, &patch)
+                .await
+                .with_context(|| format!("Failed to update approval. post_id: {}", post.id))
+        }))
+        .await
+    }
+
+    // Replies to the copy of the approval in the channel where the button was clicked
+    async fn notice(&self, interaction: &Interaction, text: &str) -> Result<()> {
+        let Some(post) = self
+            .posts
+            .iter()
+            .find(|post| post.channel_id == interaction.channel_id)
+        else {
+            bail!("Approval is not in channel: {}", interaction.channel_id);
+        };
+        self.api
+            .create_post(&json!({
+                "channel_id": post.channel_id,
+                "root_id": post.id,
+                "message": text,
+            }))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+        self.api.group_members(group_id).await
+    }
+
+    async fn next_interaction(&mut self) -> Result<Interaction> {
+        let Some(callback) = &mut self.callback else {
+            bail!("Not listening for clicks");
+        };
+        callback.next().await
+    }
+}
@@ -1,2 +1,13 @@
+pub mod chat;
+pub mod ci;
+pub mod config;
+pub mod fake_slack;
+pub mod git;
 pub mod github;
+pub mod mattermost;
+pub mod network;
 pub mod slack;
+pub mod teams;
+pub mod template;
+pub mod termination;
+pub mod terraform;
@@ -0,0 +1,112 @@
+use serde::Deserialize;
+use slack_morphism::errors::{SlackClientApiError, SlackClientError};
+
+// Extra fields of the error response
+#[derive(Deserialize)]
+struct ErrorBody {
+    needed: Option<String>,
+}
+
+// Code of the error from Slack like `channel_not_found`
+pub(super) fn api_error_code(e: &SlackClientError) -> Option<&str> {
+    match e {
+        SlackClientError::ApiError(e) => Some(e.code.as_str()),
+        _ => None,
+    }
+}
+
+// Adds what went wrong and how to fix it, which the code alone does not tell
+pub(super) fn with_diagnosis(e: SlackClientError, message: String) -> anyhow::Error {
+    let diagnosis = match &e {
+        SlackClientError::ApiError(e) => diagnose(e),
+        _ => None,
+    };
+    match diagnosis {
+        Some(diagnosis) => anyhow::Error::new(e).context(format!("{message}. {diagnosis}")),
+        None => anyhow::Error::new(e).context(message),
+    }
+}
+
+fn diagnose(e: &SlackClientApiError) -> Option<String> {
+    let diagnosis = match e.code.as_str() {
+        "not_in_channel" => {
+            "The bot is not a member of the channel. Invite the app with `/invite @<app>`, or set `join-channel: true` for public channels".into()
+        }
+        "channel_not_found" => {
+            "The channel does not exist, or it is private and the app is not in it. Check that `channel-id` is an ID like C1234567890, not a name".into()
+        }
+        "is_archived" => {
+            "The channel is archived. Unarchive it or change `channel-id`".into()
+        }
+        "method_not_supported_for_channel_type" => {
+            "Private channels cannot be joined. Invite the app with `/invite @<app>`".into()
+        }
+        "missing_scope" => {
+            // NOTE: Slack tells the scope that was needed in the body
+            let needed = e
+                .http_response_body
+                .as_deref()
+                .and_then(|body| serde_json::from_str::<ErrorBody>(body).ok())
+                .and_then(|body| body.needed)
+                .map_or_else(|| "a scope".into(), |needed| format!("`{needed}`"));
+            format!(
+                "The bot token is missing {needed}. Add it on the OAuth & Permissions page and reinstall the app"
+            )
+        }
+        "invalid_auth" | "not_authed" | "token_revoked" | "token_expired" | "account_inactive" => {
+            "The bot token is invalid or revoked. Check that `bot-token` is the Bot User OAuth Token starting with `xoxb-`".into()
+        }
+        _ => return None,
+    };
+    Some(diagnosis)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn api_error(code: &str, body: Option<&str>) -> SlackClientError {
+        SlackClientError::ApiError(SlackClientApiError {
+            code: code.into(),
+            errors: None,
+            warnings: None,
+            http_response_body: body.map(|body| body.into()),
+        })
+    }
+
+    #[rstest]
+    #[case::not_in_channel("not_in_channel", None, Some("The bot is not a member of the channel"))]
+    #[case::needed_scope(
+        "missing_scope",
+        Some(
+            r#"{"ok":false,"error":"missing_scope","needed":"chat:write","provided":"im:write"}"#
+        ),
+        Some("The bot token is missing `chat:write`")
+    )]
+    #[case::unknown_scope("missing_scope", None, Some("The bot token is missing a scope"))]
+    #[case::invalid_auth("invalid_auth", None, Some("The bot token is invalid or revoked"))]
+    #[case::unknown("ratelimited", None, None)]
+    fn should_diagnose(
+        #[case] code: &str,
+        #[case] body: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let actual = with_diagnosis(api_error(code, body), "Failed to post message".into());
+
+        match expected {
+            Some(expected) => {
+                assert!(actual.to_string().starts_with("Failed to post message. "));
+                assert!(actual.to_string().contains(expected));
+            }
+            None => assert_eq!(actual.to_string(), "Failed to post message"),
+        }
+        assert_eq!(
+            actual
+                .downcast_ref::<SlackClientError>()
+                .and_then(api_error_code),
+            Some(code)
+        );
+    }
+}
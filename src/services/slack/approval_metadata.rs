@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use slack_morphism::SlackMessageMetadata;
+
+use crate::services::ci::CiInfo;
+
+pub const APPROVAL_EVENT_TYPE: &str = "slack_approval";
+
+const KEY_FIELD: &str = "key";
+const RUN_ID_FIELD: &str = "run_id";
+const RUN_NUMBER_FIELD: &str = "run_number";
+const STATUS_FIELD: &str = "status";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Superseded,
+    Cancelled,
+    TimedOut,
+}
+
+impl ApprovalStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Rejected => "rejected",
+            ApprovalStatus::Superseded => "superseded",
+            ApprovalStatus::Cancelled => "cancelled",
+            ApprovalStatus::TimedOut => "timed_out",
+        }
+    }
+
+    fn parse(v: &str) -> Option<Self> {
+        match v {
+            "pending" => Some(ApprovalStatus::Pending),
+            "approved" => Some(ApprovalStatus::Approved),
+            "rejected" => Some(ApprovalStatus::Rejected),
+            "superseded" => Some(ApprovalStatus::Superseded),
+            "cancelled" => Some(ApprovalStatus::Cancelled),
+            "timed_out" => Some(ApprovalStatus::TimedOut),
+            _ => None,
+        }
+    }
+}
+
+// Attached to the approval message so that later runs can find it in the channel history
+#[derive(Debug, PartialEq, Clone)]
+pub struct ApprovalMetadata {
+    // Identifies the approvals which supersede each other (repository, workflow and job)
+    // NOTE: Jobs of a matrix share the name, so approvals of the same job in other matrix entries are superseded too
+    pub key: String,
+    pub run_id: String,
+    pub run_number: String,
+    pub status: ApprovalStatus,
+}
+
+impl ApprovalMetadata {
+    pub fn pending(ci_info: &dyn CiInfo) -> Self {
+        Self {
+            key: format!(
+                "{}/{}/{}",
+                ci_info.repository(),
+                ci_info.workflow(),
+                ci_info.job()
+            ),
+            run_id: ci_info.run_id().into(),
+            run_number: ci_info.run_number().into(),
+            status: ApprovalStatus::Pending,
+        }
+    }
+
+    pub fn with_status(&self, status: ApprovalStatus) -> Self {
+        Self {
+            status,
+            ..self.clone()
+        }
+    }
+
+    pub fn to_slack_metadata(&self) -> SlackMessageMetadata {
+        SlackMessageMetadata::new(APPROVAL_EVENT_TYPE.into()).with_event_payload(HashMap::from([
+            (KEY_FIELD.into(), self.key.clone()),
+            (RUN_ID_FIELD.into(), self.run_id.clone()),
+            (RUN_NUMBER_FIELD.into(), self.run_number.clone()),
+            (STATUS_FIELD.into(), self.status.as_str().into()),
+        ]))
+    }
+
+    // Returns None when the metadata was not posted by this action
+    pub fn from_slack_metadata(metadata: &SlackMessageMetadata) -> Option<Self> {
+        if metadata.event_type != APPROVAL_EVENT_TYPE {
+            return None;
+        }
+
+        let payload = metadata.event_payload.as_ref()?;
+        Some(Self {
+            key: payload.get(KEY_FIELD)?.clone(),
+            run_id: payload.get(RUN_ID_FIELD)?.clone(),
+            run_number: payload.get(RUN_NUMBER_FIELD)?.clone(),
+            status: ApprovalStatus::parse(payload.get(STATUS_FIELD)?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+    use rstest::rstest;
+
+    fn metadata(status: ApprovalStatus) -> ApprovalMetadata {
+        ApprovalMetadata {
+            key: "octocat/Hello-World/Deploy".into(),
+            run_id: "42".into(),
+            run_number: "7".into(),
+            status,
+        }
+    }
+
+    #[rstest]
+    #[case(ApprovalStatus::Pending)]
+    #[case(ApprovalStatus::Approved)]
+    #[case(ApprovalStatus::Rejected)]
+    #[case(ApprovalStatus::Superseded)]
+    #[case(ApprovalStatus::Cancelled)]
+    #[case(ApprovalStatus::TimedOut)]
+    fn should_round_trip_slack_metadata(#[case] status: ApprovalStatus) {
+        let expected = metadata(status);
+        let actual = ApprovalMetadata::from_slack_metadata(&expected.to_slack_metadata());
+        assert_eq!(actual, Some(expected));
+    }
+
+    #[test]
+    fn should_key_by_job() {
+        let actual = ApprovalMetadata::pending(&GitHubInfo::for_test());
+        assert_eq!(actual.key, "octocat/Hello-World/Deploy/approval");
+    }
+
+    #[test]
+    fn should_ignore_other_event_types() {
+        let mut slack_metadata = metadata(ApprovalStatus::Pending).to_slack_metadata();
+        slack_metadata.event_type = "other_event".into();
+        assert_eq!(ApprovalMetadata::from_slack_metadata(&slack_metadata), None);
+    }
+
+    #[test]
+    fn should_ignore_incomplete_payload() {
+        let slack_metadata = SlackMessageMetadata::new(APPROVAL_EVENT_TYPE.into())
+            .with_event_payload(HashMap::from([(KEY_FIELD.into(), "key".into())]));
+        assert_eq!(ApprovalMetadata::from_slack_metadata(&slack_metadata), None);
+    }
+}
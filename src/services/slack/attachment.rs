@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use slack_morphism::prelude::*;
+use tracing::info;
+
+use super::{PostedMessage, retry};
+
+// File uploaded into the thread of the approval
+#[derive(Debug, PartialEq, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content: Vec<u8>,
+}
+
+// Reads every file before the approval is posted so that a missing file fails early
+pub fn read_attachments(paths: &[String]) -> Result<Vec<Attachment>> {
+    paths
+        .iter()
+        .map(|path| {
+            let content = std::fs::read(path)
+                .with_context(|| format!("Failed to read attachment: {path}"))?;
+            // NOTE: Slack rejects empty files
+            if content.is_empty() {
+                bail!("Attachment is empty: {path}");
+            }
+            let filename = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into())
+                .unwrap_or_else(|| path.clone());
+
+            Ok(Attachment { filename, content })
+        })
+        .collect()
+}
+
+// Uploads the files into the thread of every copy
+// NOTE: Approvers would decide without the files, so a failure fails the step
+pub async fn upload_attachments<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    messages: &[PostedMessage],
+    attachments: &[Attachment],
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    for message in messages {
+        upload_to_thread(session, message, attachments).await?;
+    }
+
+    Ok(())
+}
+
+// Files upload v2: each file is uploaded to its own URL, and then all of them are shared at once
+async fn upload_to_thread<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    message: &PostedMessage,
+    attachments: &[Attachment],
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let mut files = vec![];
+    for attachment in attachments {
+        let req = SlackApiFilesGetUploadUrlExternalRequest::new(
+            attachment.filename.clone(),
+            attachment.content.len(),
+        );
+        let res = retry::with_retry("files.getUploadURLExternal", || {
+            session.get_upload_url_external(&req)
+        })
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to get upload URL. Have you added `files:write` scope? filename: {}",
+                attachment.filename
+            )
+        })?;
+        let req = SlackApiFilesUploadViaUrlRequest::new(
+            res.upload_url,
+            attachment.content.clone(),
+            "application/octet-stream".into(),
+        );
+        retry::with_retry("files.upload", || session.files_upload_via_url(&req))
+            .await
+            .with_context(|| format!("Failed to upload file. filename: {}", attachment.filename))?;
+
+        files.push(SlackApiFilesComplete::new(res.file_id).with_title(attachment.filename.clone()));
+    }
+
+    let thread_ts = message.thread_ts();
+    let req = SlackApiFilesCompleteUploadExternalRequest::new(files)
+        .with_channel_id(message.channel_id.clone())
+        .with_thread_ts(thread_ts.clone());
+    retry::with_retry_once("files.completeUploadExternal", || {
+        session.files_complete_upload_external(&req)
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "Failed to share files. channel_id: {}, thread_ts: {thread_ts}",
+            message.channel_id
+        )
+    })?;
+
+    info!(
+        "Attachments uploaded: {}, channel_id: {}",
+        attachments.len(),
+        message.channel_id
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_attachments() {
+        let dir = std::env::temp_dir().join("slack-approval-should-read-attachments");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plan.txt");
+        std::fs::write(&path, "+ aws_s3_bucket.assets").unwrap();
+
+        let actual = read_attachments(&[path.to_string_lossy().into()]).unwrap();
+
+        assert_eq!(
+            actual,
+            vec![Attachment {
+                filename: "plan.txt".into(),
+                content: b"+ aws_s3_bucket.assets".to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_fail_to_read_empty_attachment() {
+        let path = std::env::temp_dir().join("slack-approval-should-fail-to-read-empty.txt");
+        std::fs::write(&path, "").unwrap();
+        let path: String = path.to_string_lossy().into();
+
+        let actual = read_attachments(std::slice::from_ref(&path)).unwrap_err();
+
+        assert_eq!(actual.to_string(), format!("Attachment is empty: {path}"));
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use slack_morphism::prelude::*;
+
+// https://docs.slack.dev/reference/block-kit/blocks
+pub const MAX_BLOCKS: usize = 50;
+pub const MAX_SECTION_TEXT: usize = 3000;
+pub const MAX_SECTION_FIELDS: usize = 10;
+pub const MAX_SECTION_FIELD_TEXT: usize = 2000;
+pub const MAX_HEADER_TEXT: usize = 150;
+pub const MAX_CONTEXT_ELEMENTS: usize = 10;
+pub const MAX_ACTIONS_ELEMENTS: usize = 25;
+pub const MAX_BLOCK_ID: usize = 255;
+
+// Checks the limits which make `chat.postMessage` fail with `invalid_blocks`
+// Every violation is reported at once so that all of them can be fixed
+pub fn validate_blocks(blocks: &[SlackBlock]) -> Result<()> {
+    let blocks = serde_json::to_value(blocks).with_context(|| "Failed to serialize blocks")?;
+    let blocks = blocks.as_array().cloned().unwrap_or_default();
+
+    let mut violations = vec![];
+    if blocks.len() > MAX_BLOCKS {
+        violations.push(format!(
+            "message has {} blocks, but the maximum is {MAX_BLOCKS}",
+            blocks.len()
+        ));
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        let block_type = block["type"].as_str().unwrap_or_default();
+        let location = format!("blocks[{i}] ({block_type})");
+        violations.extend(
+            validate_block(block_type, block)
+                .into_iter()
+                .map(|violation| format!("{location}: {violation}")),
+        );
+    }
+
+    if !violations.is_empty() {
+        bail!(
+            "Message exceeds Slack limits:\n{}",
+            violations
+                .iter()
+                .map(|violation| format!("- {violation}"))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+fn validate_block(block_type: &str, block: &Value) -> Vec<String> {
+    let mut violations = vec![];
+
+    check_length(
+        &mut violations,
+        "block_id",
+        &block["block_id"],
+        MAX_BLOCK_ID,
+    );
+    match block_type {
+        "section" => {
+            check_length(
+                &mut violations,
+                "text",
+                &block["text"]["text"],
+                MAX_SECTION_TEXT,
+            );
+            if let Some(fields) = block["fields"].as_array() {
+                check_count(&mut violations, "fields", fields, MAX_SECTION_FIELDS);
+                for (i, field) in fields.iter().enumerate() {
+                    check_length(
+                        &mut violations,
+                        &format!("fields[{i}]"),
+                        &field["text"],
+                        MAX_SECTION_FIELD_TEXT,
+                    );
+                }
+            }
+        }
+        "header" => check_length(
+            &mut violations,
+            "text",
+            &block["text"]["text"],
+            MAX_HEADER_TEXT,
+        ),
+        "context" => {
+            if let Some(elements) = block["elements"].as_array() {
+                check_count(&mut violations, "elements", elements, MAX_CONTEXT_ELEMENTS);
+            }
+        }
+        "actions" => {
+            if let Some(elements) = block["elements"].as_array() {
+                check_count(&mut violations, "elements", elements, MAX_ACTIONS_ELEMENTS);
+            }
+        }
+        _ => {}
+    }
+
+    violations
+}
+
+fn check_length(violations: &mut Vec<String>, name: &str, value: &Value, max: usize) {
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count();
+        if length > max {
+            violations.push(format!(
+                "{name} has {length} characters, but the maximum is {max}"
+            ));
+        }
+    }
+}
+
+fn check_count(violations: &mut Vec<String>, name: &str, values: &[Value], max: usize) {
+    if values.len() > max {
+        violations.push(format!(
+            "{name} has {} items, but the maximum is {max}",
+            values.len()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_blocks_within_limits() {
+        let blocks: Vec<SlackBlock> = slack_blocks![
+            some_into(SlackHeaderBlock::new(pt!("Deploy"))),
+            some_into(SlackSectionBlock::new().with_text(md!("a".repeat(MAX_SECTION_TEXT))))
+        ];
+        assert!(validate_blocks(&blocks).is_ok());
+    }
+
+    #[test]
+    fn should_report_every_violation() {
+        let mut blocks: Vec<SlackBlock> = slack_blocks![
+            some_into(SlackHeaderBlock::new(pt!("a".repeat(MAX_HEADER_TEXT + 1)))),
+            some_into(
+                SlackSectionBlock::new()
+                    .with_text(md!("a".repeat(MAX_SECTION_TEXT + 1)))
+                    .with_fields(vec![md!("field"); MAX_SECTION_FIELDS + 1])
+            )
+        ];
+        blocks.extend(vec![SlackDividerBlock::new().into(); MAX_BLOCKS - 1]);
+
+        let actual = validate_blocks(&blocks).map_err(|e| e.to_string());
+
+        assert_eq!(
+            actual,
+            Err([
+                "Message exceeds Slack limits:",
+                "- message has 51 blocks, but the maximum is 50",
+                "- blocks[0] (header): text has 151 characters, but the maximum is 150",
+                "- blocks[1] (section): text has 3001 characters, but the maximum is 3000",
+                "- blocks[1] (section): fields has 11 items, but the maximum is 10",
+            ]
+            .join("\n"))
+        );
+    }
+}
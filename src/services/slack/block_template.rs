@@ -0,0 +1,136 @@
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use slack_morphism::prelude::*;
+
+use super::content::build_actions_block;
+use crate::services::template::TemplateContext;
+
+// Builds the message from a Block Kit template file in JSON or YAML
+// The template is either an array of blocks or an object with `blocks` (and optional `text`)
+// Placeholders in every string are resolved, and the buttons are appended at the end if needed
+pub fn build_template_content(
+    path: &str,
+    context: &TemplateContext,
+    with_actions: bool,
+) -> Result<SlackMessageContent> {
+    let template = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read template file: {path}"))?;
+    build_content_from_template(&template, context, with_actions)
+        .with_context(|| format!("Invalid template file: {path}"))
+}
+
+fn build_content_from_template(
+    template: &str,
+    context: &TemplateContext,
+    with_actions: bool,
+) -> Result<SlackMessageContent> {
+    // NOTE: JSON can also be parsed since YAML is a superset of JSON
+    let template: Value = serde_yaml::from_str(template)
+        .with_context(|| "Failed to parse template as JSON or YAML")?;
+    let template = render_value(template, context)?;
+
+    let (blocks, text) = match template {
+        Value::Array(_) => (template, None),
+        Value::Object(mut object) => {
+            let Some(blocks) = object.remove("blocks") else {
+                bail!("Template object must have `blocks`");
+            };
+            let text = match object.remove("text") {
+                None => None,
+                Some(Value::String(text)) => Some(text),
+                Some(_) => bail!("`text` in template must be a string"),
+            };
+            (blocks, text)
+        }
+        _ => bail!("Template must be an array of blocks or an object with `blocks`"),
+    };
+
+    let mut blocks: Vec<SlackBlock> = serde_json::from_value(blocks)
+        .with_context(|| "Template contains blocks which are not valid Block Kit")?;
+    if with_actions {
+        blocks.push(build_actions_block());
+    }
+
+    Ok(SlackMessageContent::new()
+        .opt_text(text)
+        .with_blocks(blocks))
+}
+
+fn render_value(value: Value, context: &TemplateContext) -> Result<Value> {
+    Ok(match value {
+        Value::String(v) => Value::String(context.render(&v)?),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| render_value(v, context))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(k, v)| Ok((k, render_value(v, context)?)))
+                .collect::<Result<_>>()?,
+        ),
+        _ => value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        r#"[{"type": "section", "text": {"type": "mrkdwn", "text": "By ${{ github.actor }}"}}]"#,
+        None
+    )]
+    #[case(
+        r#"
+text: Deploy ${{ github.repository }}
+blocks:
+  - type: section
+    text:
+      type: mrkdwn
+      text: By ${{ github.actor }}
+"#,
+        Some("Deploy octocat/Hello-World")
+    )]
+    fn should_build_content_from_template(#[case] template: &str, #[case] text: Option<&str>) {
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content_from_template(template, &context, true).unwrap();
+
+        let expected: Vec<SlackBlock> = vec![
+            SlackSectionBlock::new().with_text(md!("By octocat")).into(),
+            build_actions_block(),
+        ];
+        assert_eq!(actual.text, text.map(|text| text.into()));
+        assert_eq!(actual.blocks, Some(expected));
+    }
+
+    #[rstest]
+    #[case("[", "Failed to parse template as JSON or YAML")]
+    #[case(r#"{"text": "no blocks"}"#, "Template object must have `blocks`")]
+    #[case(
+        r#""blocks""#,
+        "Template must be an array of blocks or an object with `blocks`"
+    )]
+    #[case(
+        r#"[{"type": "unknown"}]"#,
+        "Template contains blocks which are not valid Block Kit"
+    )]
+    #[case(
+        r#"[{"type": "divider", "block_id": "${{ github.token }}"}]"#,
+        "Unknown placeholder: github.token"
+    )]
+    fn should_reject_invalid_template(#[case] template: &str, #[case] expected: &str) {
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content_from_template(template, &context, true).unwrap_err();
+        assert_eq!(actual.to_string(), expected);
+    }
+}
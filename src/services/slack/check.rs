@@ -0,0 +1,435 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use slack_morphism::prelude::*;
+use tracing::{error, info, warn};
+
+use super::api_error::api_error_code;
+use super::client::{self, SlackHttpClient};
+use super::socket_mode::SocketMode;
+use crate::services::github::github_inputs::{GitHubInputs, Mode};
+use crate::services::network::{self, NetworkOptions};
+
+// Counts the failures while every check runs to the end
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn pass(&self, message: &str) {
+        info!("✅ {message}");
+    }
+
+    fn warn(&self, message: &str) {
+        warn!("⚠️ {message}");
+    }
+
+    fn fail(&mut self, message: &str) {
+        error!("❌ {message}");
+        self.failures += 1;
+    }
+}
+
+// https://api.slack.com/methods/auth.test
+#[derive(Deserialize)]
+struct AuthTestResponse {
+    ok: bool,
+    error: Option<String>,
+    user: Option<String>,
+    team: Option<String>,
+}
+
+struct AuthTest {
+    user: String,
+    team: String,
+    // Granted to the token, which is only in the header
+    scopes: Vec<String>,
+}
+
+// Validates the tokens, scopes, channels, users and groups without posting anything
+pub async fn handle_slack_check(github_inputs: &GitHubInputs) -> Result<()> {
+    let mut report = Report::default();
+    let client = Arc::new(client::new_client(&github_inputs.network)?);
+
+    let auth = match auth_test(&github_inputs.bot_token, &github_inputs.network).await? {
+        Ok(auth) => auth,
+        Err(code) => {
+            report.fail(&format!("Bot token is invalid: {code}"));
+            bail!("Check failed. Fix the bot token first");
+        }
+    };
+    report.pass(&format!(
+        "Bot token is valid: {} in {}",
+        auth.user, auth.team
+    ));
+
+    let token = SlackApiToken::new(github_inputs.bot_token.clone().into());
+    let session = client.open_session(&token);
+    let channels = if github_inputs.post_to.channel {
+        check_channels(
+            &mut report,
+            &session,
+            &github_inputs.channel_ids,
+            github_inputs.join_channel,
+        )
+        .await
+    } else {
+        vec![]
+    };
+    // NOTE: Channels are checked first, as reading the history of private channels needs another scope
+    check_scopes(
+        &mut report,
+        "Bot token",
+        &auth.scopes,
+        &required_scopes(github_inputs, &channels),
+    );
+    check_users(&mut report, &session, github_inputs).await;
+    check_groups(&mut report, &session, github_inputs).await;
+
+    // NOTE: Notify mode does not listen for the buttons
+    match (&github_inputs.app_token, github_inputs.mode) {
+        (Some(app_token), _) => {
+            check_app_token(
+                &mut report,
+                client.clone(),
+                app_token,
+                &github_inputs.network,
+            )
+            .await?
+        }
+        (None, Mode::Approval) => report.fail("App token is not set"),
+        (None, Mode::Notify) => {}
+    }
+
+    if report.failures > 0 {
+        bail!("Check failed with {} problem(s)", report.failures);
+    }
+    info!("All checks passed");
+
+    Ok(())
+}
+
+// The outer error is for the request itself, and the inner one is the error code from Slack
+async fn auth_test(token: &str, network: &NetworkOptions) -> Result<Result<AuthTest, String>> {
+    let url = format!("{}/auth.test", client::api_url(network));
+    let res = network::http_client(network)?
+        .post(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .with_context(|| format!("Failed to call auth.test. url: {url}"))?;
+    let scopes = res
+        .headers()
+        .get("x-oauth-scopes")
+        .and_then(|v| v.to_str().ok())
+        .map(to_scopes)
+        .unwrap_or_default();
+    let body: AuthTestResponse = res
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse auth.test. url: {url}"))?;
+
+    if !body.ok {
+        return Ok(Err(body.error.unwrap_or_else(|| "unknown_error".into())));
+    }
+    Ok(Ok(AuthTest {
+        user: body.user.unwrap_or_default(),
+        team: body.team.unwrap_or_default(),
+        scopes,
+    }))
+}
+
+fn to_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(|scope| scope.trim().to_string())
+        .filter(|scope| !scope.is_empty())
+        .collect()
+}
+
+// Scope which a call needs, satisfied by any of the alternatives
+type Scope = &'static [&'static str];
+
+// Scopes of the bot token for the calls which the approval makes with the inputs
+// `channels` is whether each channel is private, and None when it is unknown
+fn required_scopes(github_inputs: &GitHubInputs, channels: &[Option<bool>]) -> Vec<Scope> {
+    // chat.postMessage and chat.update
+    let mut scopes: Vec<Scope> = vec![&["chat:write"]];
+    // conversations.join
+    if github_inputs.join_channel {
+        scopes.push(&["channels:join"]);
+    }
+    // conversations.open
+    if github_inputs.post_to.direct_message {
+        scopes.push(&["im:write"]);
+    }
+    // usergroups.users.list
+    if !github_inputs.authorized_groups.is_empty() {
+        scopes.push(&["usergroups:read"]);
+    }
+    // conversations.history
+    if github_inputs.supersede_pending || github_inputs.thread_match.is_some() {
+        for is_private in channels {
+            let scope: Scope = match is_private {
+                Some(false) => &["channels:history"],
+                Some(true) => &["groups:history"],
+                None => &["channels:history", "groups:history"],
+            };
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+    }
+    // files.getUploadURLExternal and files.completeUploadExternal
+    if !github_inputs.attachments.is_empty() {
+        scopes.push(&["files:write"]);
+    }
+    scopes
+}
+
+fn missing_scopes(granted: &[String], required: &[Scope]) -> Vec<Scope> {
+    required
+        .iter()
+        .filter(|scope| {
+            !scope
+                .iter()
+                .any(|scope| granted.iter().any(|granted| granted == scope))
+        })
+        .copied()
+        .collect()
+}
+
+fn check_scopes(report: &mut Report, name: &str, granted: &[String], required: &[Scope]) {
+    let to_text = |scopes: &[Scope]| {
+        scopes
+            .iter()
+            .map(|scope| scope.join(" or "))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let missing = missing_scopes(granted, required);
+    if missing.is_empty() {
+        report.pass(&format!("{name} has scopes: {}", to_text(required)));
+    } else {
+        report.fail(&format!(
+            "{name} is missing scopes: {}. Add them in OAuth & Permissions and reinstall the app",
+            to_text(&missing)
+        ));
+    }
+}
+
+async fn check_channels<SCHC>(
+    report: &mut Report,
+    session: &SlackClientSession<'_, SCHC>,
+    channel_ids: &[String],
+    join_channel: bool,
+) -> Vec<Option<bool>>
+where
+    SCHC: SlackClientHttpConnector + Send,
+{
+    let mut channels = vec![];
+    for channel_id in channel_ids {
+        let res = session
+            .conversations_info(&SlackApiConversationsInfoRequest::new(
+                channel_id.clone().into(),
+            ))
+            .await;
+        channels.push(
+            res.as_ref()
+                .ok()
+                .and_then(|res| res.channel.flags.is_private),
+        );
+        match res {
+            Ok(res) if res.channel.flags.is_member == Some(true) => {
+                report.pass(&format!("Bot is a member of channel: {channel_id}"))
+            }
+            Ok(_) if join_channel => report.pass(&format!(
+                "Bot is not a member of channel, and joins it on posting: {channel_id}"
+            )),
+            Ok(_) => report.fail(&format!(
+                "Bot is not a member of channel: {channel_id}. Invite the app with `/invite`"
+            )),
+            Err(e) => match api_error_code(&e) {
+                Some("missing_scope") => report.warn(&format!(
+                    "Channel is not checked without `channels:read` and `groups:read`: {channel_id}"
+                )),
+                Some("channel_not_found") => report.fail(&format!(
+                    "Channel is not found or is private without the app: {channel_id}"
+                )),
+                _ => report.fail(&format!("Failed to get channel: {channel_id}: {e}")),
+            },
+        }
+    }
+
+    channels
+}
+
+async fn check_users<SCHC>(
+    report: &mut Report,
+    session: &SlackClientSession<'_, SCHC>,
+    github_inputs: &GitHubInputs,
+) where
+    SCHC: SlackClientHttpConnector + Send,
+{
+    let mut seen = HashSet::new();
+    let user_ids = github_inputs
+        .mention_to_users
+        .iter()
+        .chain(&github_inputs.authorized_users)
+        .filter(|user_id| seen.insert(*user_id));
+    for user_id in user_ids {
+        let res = session
+            .users_info(&SlackApiUsersInfoRequest::new(user_id.clone().into()))
+            .await;
+        match res {
+            Ok(res) if res.user.deleted == Some(true) => {
+                report.fail(&format!("User is deactivated: {user_id}"))
+            }
+            Ok(_) => report.pass(&format!("User exists: {user_id}")),
+            Err(e) => match api_error_code(&e) {
+                Some("missing_scope") => report.warn(&format!(
+                    "User is not checked without `users:read`: {user_id}"
+                )),
+                Some("user_not_found") => report.fail(&format!("User is not found: {user_id}")),
+                _ => report.fail(&format!("Failed to get user: {user_id}: {e}")),
+            },
+        }
+    }
+}
+
+async fn check_groups<SCHC>(
+    report: &mut Report,
+    session: &SlackClientSession<'_, SCHC>,
+    github_inputs: &GitHubInputs,
+) where
+    SCHC: SlackClientHttpConnector + Send,
+{
+    let mut seen = HashSet::new();
+    let group_ids: Vec<&String> = github_inputs
+        .mention_to_groups
+        .iter()
+        .chain(&github_inputs.authorized_groups)
+        .filter(|group_id| seen.insert(*group_id))
+        .collect();
+    if group_ids.is_empty() {
+        return;
+    }
+
+    let res = session
+        .usergroups_list(&SlackApiUserGroupsListRequest::new().with_include_disabled(true))
+        .await;
+    let groups = match res {
+        Ok(res) => res.usergroups,
+        Err(e) => {
+            match api_error_code(&e) {
+                // NOTE: Only `authorized-groups` needs the scope, and it is reported with the others
+                Some("missing_scope") => {
+                    report.warn("Groups are not checked without `usergroups:read`")
+                }
+                _ => report.fail(&format!("Failed to list groups: {e}")),
+            }
+            return;
+        }
+    };
+    for group_id in group_ids {
+        match groups.iter().find(|group| &group.id.0 == group_id) {
+            // NOTE: Active groups have `0` as `date_delete`
+            Some(group)
+                if group
+                    .date_delete
+                    .as_ref()
+                    .is_some_and(|date| date.0.timestamp() > 0) =>
+            {
+                report.fail(&format!("Group is disabled: {group_id}"))
+            }
+            Some(_) => report.pass(&format!("Group exists: {group_id}")),
+            None => report.fail(&format!("Group is not found: {group_id}")),
+        }
+    }
+}
+
+// Opens a Socket Mode connection and closes it once Slack says hello
+async fn check_app_token(
+    report: &mut Report,
+    client: Arc<SlackHttpClient>,
+    app_token: &str,
+    network: &NetworkOptions,
+) -> Result<()> {
+    let auth = match auth_test(app_token, network).await? {
+        Ok(auth) => auth,
+        Err(code) => {
+            report.fail(&format!("App token is invalid: {code}"));
+            return Ok(());
+        }
+    };
+    report.pass("App token is valid");
+    // NOTE: App-level tokens have no scopes in the header on some workspaces
+    if !auth.scopes.is_empty() {
+        check_scopes(report, "App token", &auth.scopes, &[&["connections:write"]]);
+    }
+
+    match SocketMode::probe(client, &app_token.to_string().into(), network).await {
+        Ok(()) => report.pass("Socket Mode connection was opened and closed"),
+        Err(e) => report.fail(&format!("{e:#}")),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::services::config::Config;
+    use crate::services::github::github_inputs::read_github_inputs;
+
+    #[rstest]
+    #[case::minimal(&[], vec![], vec!["chat:write"])]
+    #[case::direct_message(&[("post-to", "channel,direct-message"), ("authorized-users", "U123")], vec![], vec!["chat:write", "im:write"])]
+    #[case::join_channel(&[("join-channel", "true")], vec![], vec!["chat:write", "channels:join"])]
+    #[case::groups(&[("authorized-groups", "S123")], vec![], vec!["chat:write", "usergroups:read"])]
+    #[case::mention_groups(&[("mention-to-groups", "S123")], vec![], vec!["chat:write"])]
+    #[case::supersede_public(&[("supersede-pending", "true")], vec![Some(false)], vec!["chat:write", "channels:history"])]
+    #[case::supersede_private(&[("supersede-pending", "true")], vec![Some(true)], vec!["chat:write", "groups:history"])]
+    #[case::supersede_unknown(&[("supersede-pending", "true")], vec![None, Some(false)], vec!["chat:write", "channels:history or groups:history", "channels:history"])]
+    #[case::attachments(&[("attachments", "plan.txt")], vec![], vec!["chat:write", "files:write"])]
+    fn should_require_scopes(
+        #[case] values: &[(&str, &str)],
+        #[case] channels: Vec<Option<bool>>,
+        #[case] expected: Vec<&str>,
+    ) {
+        let mut config_values = vec![
+            ("bot-token", "xoxb-123"),
+            ("app-token", "xapp-123"),
+            ("channel-id", "C123"),
+        ];
+        config_values.extend_from_slice(values);
+        let github_inputs = read_github_inputs(&Config::from_values(&config_values)).unwrap();
+
+        let actual: Vec<String> = required_scopes(&github_inputs, &channels)
+            .iter()
+            .map(|scope| scope.join(" or "))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case::all_granted("chat:write, groups:history", vec![])]
+    #[case::missing("chat:write", vec!["channels:history or groups:history"])]
+    #[case::empty("", vec!["chat:write", "channels:history or groups:history"])]
+    fn should_find_missing_scopes(#[case] header: &str, #[case] expected: Vec<&str>) {
+        let actual: Vec<String> = missing_scopes(
+            &to_scopes(header),
+            &[&["chat:write"], &["channels:history", "groups:history"]],
+        )
+        .iter()
+        .map(|scope| scope.join(" or "))
+        .collect();
+        assert_eq!(actual, expected);
+    }
+}
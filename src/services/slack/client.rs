@@ -0,0 +1,38 @@
+use anyhow::Result;
+use hyper_rustls::HttpsConnector;
+use slack_morphism::prelude::*;
+
+use crate::services::network::{self, NetworkOptions, ProxyConnector};
+
+pub(super) type SlackHttpClient =
+    SlackClient<SlackClientHyperConnector<HttpsConnector<ProxyConnector>>>;
+
+// Slack client which goes through the proxy and waits for the rate tier of each method
+// NOTE: Rate limited calls are retried by `with_retry`, not by the client
+pub(super) fn new_client(network: &NetworkOptions) -> Result<SlackHttpClient> {
+    let builder = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(network::tls_config(network)?.as_ref().clone());
+    // NOTE: Plain HTTP is allowed only when it is asked for, like for a fake Slack on localhost
+    let builder = if api_url(network).starts_with("http://") {
+        builder.https_or_http()
+    } else {
+        builder.https_only()
+    };
+    let https_connector = builder
+        .enable_http1()
+        .enable_http2()
+        .wrap_connector(ProxyConnector);
+    let connector = SlackClientHyperConnector::with_connector(https_connector)
+        .with_slack_api_url(api_url(network))
+        .with_rate_control(SlackApiRateControlConfig::new());
+
+    Ok(SlackClient::new(connector))
+}
+
+// Base URL of the Web API, which Socket Mode follows as Slack returns its URL
+pub(super) fn api_url(network: &NetworkOptions) -> &str {
+    network
+        .slack_api_url
+        .as_deref()
+        .unwrap_or(SlackClientHttpApiUri::SLACK_API_URI_STR)
+}
@@ -0,0 +1,192 @@
+use slack_morphism::prelude::*;
+
+use super::block_limits::MAX_SECTION_TEXT;
+use super::content::escape_mrkdwn;
+use crate::services::ci::CiInfo;
+use crate::services::git::CommitRange;
+
+// Number of commits listed in the thread
+pub const MAX_COMMITS: usize = 100;
+
+// Summaries and authors are cut so that a line always fits in a section, even after escaping
+const MAX_SUMMARY_CHARS: usize = 200;
+const MAX_AUTHOR_CHARS: usize = 100;
+
+pub fn build_commits_field(range: &CommitRange) -> SlackBlockText {
+    let unit = if range.total == 1 {
+        "commit"
+    } else {
+        "commits"
+    };
+    md!(format!(
+        "📜*Commits:*\n{} {unit} since `{}` (in thread)",
+        range.total,
+        escape_mrkdwn(&range.base)
+    ))
+}
+
+// Lists the commits in a thread reply so that the approval itself stays short
+pub fn build_commit_list_content(ci_info: &dyn CiInfo, range: &CommitRange) -> SlackMessageContent {
+    if range.commits.is_empty() {
+        return SlackMessageContent::new()
+            .with_text(format!("No commits since {}", escape_mrkdwn(&range.base)));
+    }
+
+    let mut lines: Vec<String> = range
+        .commits
+        .iter()
+        .map(|commit| {
+            let short_sha = commit.sha.get(..7).unwrap_or(&commit.sha);
+            let sha = match ci_info.commit_url(&commit.sha) {
+                Some(url) => format!("<{url}|`{short_sha}`>"),
+                None => format!("`{short_sha}`"),
+            };
+            format!(
+                "• {sha} {} — {}",
+                escape_mrkdwn(&truncate(&commit.summary, MAX_SUMMARY_CHARS)),
+                escape_mrkdwn(&truncate(&commit.author, MAX_AUTHOR_CHARS))
+            )
+        })
+        .collect();
+    if range.total > range.commits.len() {
+        lines.push(format!("…and {} more", range.total - range.commits.len()));
+    }
+
+    // NOTE: Lines are packed into sections without splitting a line
+    let mut blocks: Vec<SlackBlock> = vec![];
+    let mut section = String::new();
+    for line in lines {
+        if !section.is_empty() && section.chars().count() + line.chars().count() >= MAX_SECTION_TEXT
+        {
+            blocks.push(SlackSectionBlock::new().with_text(md!(section)).into());
+            section = String::new();
+        }
+        if !section.is_empty() {
+            section.push('\n');
+        }
+        section.push_str(&line);
+    }
+    blocks.push(SlackSectionBlock::new().with_text(md!(section)).into());
+
+    SlackMessageContent::new()
+        .with_text(format!("Commits since {}", escape_mrkdwn(&range.base)))
+        .with_blocks(blocks)
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.into();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::git::Commit;
+    use crate::services::github::github_info::GitHubInfo;
+
+    fn commit(sha: &str, summary: &str, author: &str) -> Commit {
+        Commit {
+            sha: sha.into(),
+            summary: summary.into(),
+            author: author.into(),
+        }
+    }
+
+    #[test]
+    fn should_build_commit_list() {
+        let range = CommitRange {
+            base: "v1.41.0".into(),
+            commits: vec![
+                commit("ffac537e6cbbf934", "Fix <rounding>", "monalisa"),
+                commit("6dcb09b5b57875f3", "Add payments", "octocat"),
+            ],
+            total: 3,
+        };
+
+        let actual = build_commit_list_content(&GitHubInfo::for_test(), &range);
+
+        assert_eq!(actual.text, Some("Commits since v1.41.0".into()));
+        let expected: Vec<SlackBlock> = vec![
+            SlackSectionBlock::new()
+                .with_text(md!([
+                    "• <https://github.com/octocat/Hello-World/commit/ffac537e6cbbf934|`ffac537`> Fix &lt;rounding&gt; — monalisa",
+                    "• <https://github.com/octocat/Hello-World/commit/6dcb09b5b57875f3|`6dcb09b`> Add payments — octocat",
+                    "…and 1 more",
+                ]
+                .join("\n")))
+                .into(),
+        ];
+        assert_eq!(actual.blocks, Some(expected));
+        assert_eq!(
+            build_commits_field(&range),
+            md!("📜*Commits:*\n3 commits since `v1.41.0` (in thread)")
+        );
+    }
+
+    #[test]
+    fn should_escape_base() {
+        let range = CommitRange {
+            base: "<tag>".into(),
+            commits: vec![],
+            total: 0,
+        };
+
+        let actual = build_commit_list_content(&GitHubInfo::for_test(), &range);
+
+        assert_eq!(actual.text, Some("No commits since &lt;tag&gt;".into()));
+    }
+
+    #[test]
+    fn should_split_commit_list_into_sections() {
+        let range = CommitRange {
+            base: "v1.41.0".into(),
+            commits: vec![commit("ffac537e6cbbf934", &"a".repeat(190), "octocat"); 25],
+            total: 25,
+        };
+
+        let actual = build_commit_list_content(&GitHubInfo::for_test(), &range);
+
+        let lengths: Vec<usize> = actual
+            .blocks
+            .unwrap()
+            .iter()
+            .filter_map(|block| match block {
+                SlackBlock::Section(section) => section.text.as_ref().map(|text| match text {
+                    SlackBlockText::MarkDown(text) => text.text.lines().count(),
+                    SlackBlockText::Plain(_) => 0,
+                }),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lengths, vec![10, 10, 5]);
+    }
+
+    #[test]
+    fn should_truncate_long_summary() {
+        let range = CommitRange {
+            base: "v1.41.0".into(),
+            commits: vec![commit("ffac537e6cbbf934", &"<".repeat(5000), "octocat")],
+            total: 1,
+        };
+
+        let actual = build_commit_list_content(&GitHubInfo::for_test(), &range);
+
+        let blocks = actual.blocks.unwrap();
+        let SlackBlock::Section(section) = &blocks[0] else {
+            panic!("Commits should be a section block");
+        };
+        let Some(SlackBlockText::MarkDown(text)) = &section.text else {
+            panic!("Commits should be mrkdwn");
+        };
+        assert!(text.text.chars().count() < MAX_SECTION_TEXT);
+        assert!(
+            text.text
+                .contains(&format!("{}… — octocat", "&lt;".repeat(199)))
+        );
+    }
+}
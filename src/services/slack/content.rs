@@ -0,0 +1,360 @@
+use anyhow::{Context, Result};
+use slack_morphism::prelude::*;
+
+use super::block_limits::{MAX_BLOCKS, MAX_SECTION_FIELD_TEXT, MAX_SECTION_FIELDS};
+use super::block_template::build_template_content;
+use super::commit_list::build_commits_field;
+use super::event_fields::build_event_fields;
+use super::markdown::{build_text_sections, markdown_to_mrkdwn};
+use super::terraform_summary::build_plan_blocks;
+use super::{SLACK_APPROVAL_APPROVE_ACTION_ID, SLACK_APPROVAL_REJECT_ACTION_ID};
+use crate::services::ci::CiInfo;
+use crate::services::git::CommitRange;
+use crate::services::github::github_inputs::{GitHubInputs, Mode};
+use crate::services::template::TemplateContext;
+use crate::services::terraform::TerraformPlan;
+
+const TRUNCATED_FIELD_SUFFIX: &str = "… (continued in thread)";
+
+// The approval and the rest which does not fit within Slack limits
+pub struct ApprovalContent {
+    pub message: SlackMessageContent,
+    // Posted in the thread of the approval
+    pub overflow: Vec<SlackBlock>,
+}
+
+// Escapes the control characters of Slack mrkdwn
+// https://docs.slack.dev/messaging/formatting-message-text#escaping
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn build_header(inputs: &GitHubInputs) -> String {
+    let mut header = String::new();
+    if !inputs.mention_to_users.is_empty() {
+        header.push_str(
+            &inputs
+                .mention_to_users
+                .iter()
+                .map(|user| SlackUserId::new(user.clone()).to_slack_format())
+                .collect::<Vec<String>>()
+                .join(" "),
+        );
+    }
+
+    if !inputs.mention_to_groups.is_empty() {
+        header.push_str(
+            &inputs
+                .mention_to_groups
+                .iter()
+                .map(|group| SlackUserGroupId::new(group.clone()).to_slack_format())
+                .collect::<Vec<String>>()
+                .join(" "),
+        );
+    }
+
+    header
+}
+
+fn build_fields(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+    commits: Option<&CommitRange>,
+    overflow: &mut Vec<SlackBlock>,
+) -> Result<Vec<SlackBlockText>> {
+    let labels = ci_info.labels();
+    let mut fields = vec![];
+    if !ci_info.actor().is_empty() {
+        fields.push(md!(format!("👤*Actor:*\n{}", ci_info.actor())));
+    }
+    if let Some(url) = ci_info.repository_url() {
+        fields.push(md!(format!("📦*Repository:*\n{url}")));
+    }
+    if let Some(url) = ci_info.run_url() {
+        fields.push(md!(format!("🚀*{}:*\n{url}", labels.run)));
+    }
+    if !ci_info.run_id().is_empty() {
+        let run_id = match ci_info.run_attempt() {
+            Some(attempt) if attempt != "1" => {
+                format!("{} (attempt {attempt})", ci_info.run_id())
+            }
+            _ => ci_info.run_id().into(),
+        };
+        fields.push(md!(format!("🆔*Run ID:*\n{run_id}")));
+    }
+    let workflow = [ci_info.workflow(), ci_info.job()]
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" / ");
+    if !workflow.is_empty() {
+        fields.push(md!(format!("🔄*{}:*\n{workflow}", labels.workflow)));
+    }
+    if !ci_info.runner().is_empty() {
+        fields.push(md!(format!("💻*Runner:*\n{}", ci_info.runner())));
+    }
+    fields.extend(build_event_fields(ci_info, context.event()));
+    if let Some(commits) = commits {
+        fields.push(build_commits_field(commits));
+    }
+
+    for field in &github_inputs.fields {
+        let value = context
+            .render(&field.value)
+            .with_context(|| format!("Failed to render field: {}", field.name))?;
+        let text = format!("*{}:*\n{}", field.name, value);
+        if text.chars().count() > MAX_SECTION_FIELD_TEXT {
+            fields.push(md!(truncate_field(&text)));
+            overflow.extend(build_text_sections(&text));
+        } else {
+            fields.push(md!(text));
+        }
+    }
+
+    Ok(fields)
+}
+
+fn truncate_field(text: &str) -> String {
+    let length = MAX_SECTION_FIELD_TEXT - TRUNCATED_FIELD_SUFFIX.chars().count();
+    let mut truncated: String = text.chars().take(length).collect();
+    truncated.push_str(TRUNCATED_FIELD_SUFFIX);
+
+    truncated
+}
+
+pub fn build_actions_block() -> SlackBlock {
+    SlackActionsBlock::new(slack_blocks!(
+        some_into(
+            SlackBlockButtonElement::new(SLACK_APPROVAL_APPROVE_ACTION_ID.into(), pt!("✅Approve"))
+                .with_style("primary".into())
+                .with_value("approve".into())
+        ),
+        some_into(
+            SlackBlockButtonElement::new(SLACK_APPROVAL_REJECT_ACTION_ID.into(), pt!("❌Reject"))
+                .with_style("danger".into())
+                .with_value("reject".into())
+        )
+    ))
+    .into()
+}
+
+pub fn build_content(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+    commits: Option<&CommitRange>,
+    plan: Option<&TerraformPlan>,
+) -> Result<ApprovalContent> {
+    // NOTE: Template replaces the default layout
+    if let Some(template_file) = &github_inputs.template_file {
+        return Ok(ApprovalContent {
+            message: build_template_content(
+                template_file,
+                context,
+                github_inputs.mode == Mode::Approval,
+            )?,
+            overflow: vec![],
+        });
+    }
+
+    let mut blocks: Vec<SlackBlock> = vec![];
+    let mut overflow: Vec<SlackBlock> = vec![];
+    let title = github_inputs
+        .title
+        .as_deref()
+        .map(|title| context.render(title))
+        .transpose()
+        .with_context(|| "Failed to render title")?;
+
+    if let Some(title) = &title {
+        blocks.push(SlackHeaderBlock::new(pt!(title)).into());
+    }
+
+    let header = build_header(github_inputs);
+    if !header.is_empty() {
+        blocks.push(SlackSectionBlock::new().with_text(md!(header)).into());
+    }
+
+    let mut description_blocks = vec![];
+    if let Some(description) = &github_inputs.description {
+        let description = context
+            .render(description)
+            .with_context(|| "Failed to render description")?;
+        description_blocks.extend(build_text_sections(&description));
+    }
+    if let Some(path) = &github_inputs.description_file {
+        let markdown = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read description file: {path}"))?;
+        description_blocks.extend(build_text_sections(&markdown_to_mrkdwn(&markdown)));
+    }
+
+    let mut bottom_blocks: Vec<SlackBlock> = vec![];
+    if let Some(plan) = plan {
+        bottom_blocks.extend(build_plan_blocks(plan));
+    }
+
+    let fields = build_fields(github_inputs, ci_info, context, commits, &mut overflow)?;
+    for fields in fields.chunks(MAX_SECTION_FIELDS) {
+        bottom_blocks.push(SlackSectionBlock::new().with_fields(fields.to_vec()).into());
+    }
+
+    if github_inputs.mode == Mode::Approval {
+        bottom_blocks.push(build_actions_block());
+    }
+
+    // NOTE: The description gives way to the rest, which decides what is approved
+    let fixed_blocks = blocks.len() + bottom_blocks.len();
+    if fixed_blocks + description_blocks.len() > MAX_BLOCKS {
+        let available = MAX_BLOCKS.saturating_sub(fixed_blocks + 1);
+        let rest = description_blocks.split_off(available.min(description_blocks.len()));
+        overflow.splice(0..0, rest);
+        description_blocks.push(
+            SlackContextBlock::new(vec![SlackContextBlockElement::MarkDown(
+                SlackBlockMarkDownText::new("📎Continued in thread".into()),
+            )])
+            .into(),
+        );
+    }
+    blocks.extend(description_blocks);
+    blocks.extend(bottom_blocks);
+
+    // NOTE: Text is used in notifications
+    Ok(ApprovalContent {
+        message: SlackMessageContent::new()
+            .opt_text(title)
+            .with_blocks(blocks),
+        overflow,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+
+    #[test]
+    fn should_build_content() {
+        let github_info = GitHubInfo::for_test();
+        let github_inputs = GitHubInputs::for_test(&[
+            ("mention-to-users", "U000001"),
+            ("title", "Deploy run #${{ github.run_number }}"),
+            ("description", "By *${{ github.actor }}*"),
+            ("fields", "Target: ${{ github.repository }}"),
+        ]);
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content(&github_inputs, &github_info, &context, None, None)
+            .unwrap()
+            .message;
+
+        assert_eq!(actual.text, Some("Deploy run #7".into()));
+        let blocks = actual.blocks.unwrap();
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(
+            blocks[0],
+            SlackHeaderBlock::new(pt!("Deploy run #7")).into()
+        );
+        assert_eq!(
+            blocks[1],
+            SlackSectionBlock::new().with_text(md!("<@U000001>")).into()
+        );
+        assert_eq!(
+            blocks[2],
+            SlackSectionBlock::new()
+                .with_text(md!("By *octocat*"))
+                .into()
+        );
+        let SlackBlock::Section(fields) = &blocks[3] else {
+            panic!("Fields should be a section block");
+        };
+        assert_eq!(
+            fields.fields.as_ref().unwrap().last(),
+            Some(&md!("*Target:*\noctocat/Hello-World"))
+        );
+        assert_eq!(blocks[4], build_actions_block());
+    }
+
+    #[test]
+    fn should_split_fields_into_sections() {
+        let github_info = GitHubInfo::for_test();
+        let fields = (0..5)
+            .map(|i| format!("Field {i}: value"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let github_inputs = GitHubInputs::for_test(&[("fields", &fields)]);
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content(&github_inputs, &github_info, &context, None, None)
+            .unwrap()
+            .message;
+
+        let field_counts: Vec<usize> = actual
+            .blocks
+            .unwrap()
+            .iter()
+            .filter_map(|block| match block {
+                SlackBlock::Section(section) => section.fields.as_ref().map(|f| f.len()),
+                _ => None,
+            })
+            .collect();
+        // 6 default fields, branch and commit from the event, and 5 custom fields
+        assert_eq!(field_counts, vec![10, 3]);
+    }
+
+    #[test]
+    fn should_move_what_does_not_fit_into_thread() {
+        let github_info = GitHubInfo::for_test();
+        // Each paragraph fills a section
+        let description = vec!["a".repeat(2000); 60].join("\n");
+        let fields = format!("Plan: {}", "b".repeat(MAX_SECTION_FIELD_TEXT));
+        let github_inputs = GitHubInputs::for_test(&[
+            ("mention-to-users", "U000001"),
+            ("title", "Deploy run #${{ github.run_number }}"),
+            ("description", &description),
+            ("fields", &fields),
+        ]);
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content(&github_inputs, &github_info, &context, None, None).unwrap();
+
+        let blocks = actual.message.blocks.unwrap();
+        assert_eq!(blocks.len(), MAX_BLOCKS);
+        // Header, mentions, fields and actions are kept, and the note takes one block
+        assert_eq!(
+            blocks[MAX_BLOCKS - 3],
+            SlackContextBlock::new(vec![SlackContextBlockElement::MarkDown(
+                SlackBlockMarkDownText::new("📎Continued in thread".into()),
+            )])
+            .into()
+        );
+        let SlackBlock::Section(fields) = &blocks[MAX_BLOCKS - 2] else {
+            panic!("Fields should be a section block");
+        };
+        let Some(SlackBlockText::MarkDown(field)) = fields.fields.as_ref().unwrap().last() else {
+            panic!("Field should be mrkdwn");
+        };
+        assert_eq!(field.text.chars().count(), MAX_SECTION_FIELD_TEXT);
+        assert!(field.text.ends_with(TRUNCATED_FIELD_SUFFIX));
+        // 15 sections of the description and the whole field
+        assert_eq!(actual.overflow.len(), 16);
+    }
+
+    #[test]
+    fn should_build_content_without_buttons_in_notify_mode() {
+        let github_info = GitHubInfo::for_test();
+        let github_inputs = GitHubInputs::for_test(&[("mode", "notify")]);
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_content(&github_inputs, &github_info, &context, None, None).unwrap();
+
+        let blocks = actual.message.blocks.unwrap();
+        assert!(
+            !blocks
+                .iter()
+                .any(|block| matches!(block, SlackBlock::Actions(_)))
+        );
+    }
+}
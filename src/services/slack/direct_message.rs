@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use slack_morphism::prelude::*;
+use tracing::{info, warn};
+
+use super::{PostedMessage, post_message, retry};
+
+// Sends a copy of the approval to each user
+// Users who cannot receive it are skipped so that the others can still decide
+pub async fn post_direct_messages<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    user_ids: &[SlackUserId],
+    content: &SlackMessageContent,
+) -> Vec<PostedMessage>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let mut messages = vec![];
+
+    for user_id in user_ids {
+        match post_direct_message(session, user_id, content.clone()).await {
+            Ok(message) => messages.push(message),
+            Err(e) => warn!("Skipped direct message to user: {:?}", e),
+        }
+    }
+
+    info!("Direct messages posted: {}", messages.len());
+
+    messages
+}
+
+async fn post_direct_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    user_id: &SlackUserId,
+    content: SlackMessageContent,
+) -> Result<PostedMessage>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiConversationsOpenRequest::new().with_users(vec![user_id.clone()]);
+    let res = retry::with_retry("conversations.open", || session.conversations_open(&req))
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to open direct message. Have you added `im:write` scope? user_id: {user_id}"
+            )
+        })?;
+    let channel_id = res.channel.id;
+    let ts = post_message(session, &channel_id, content, None).await?;
+
+    Ok(PostedMessage {
+        channel_id,
+        ts,
+        thread: None,
+    })
+}
@@ -0,0 +1,207 @@
+use serde_json::Value;
+use slack_morphism::prelude::*;
+
+use super::content::escape_mrkdwn;
+use crate::services::ci::CiInfo;
+
+// Describes what is being deployed depending on the event that triggered the workflow
+pub fn build_event_fields(ci_info: &dyn CiInfo, event: Option<&Value>) -> Vec<SlackBlockText> {
+    let null = Value::Null;
+    let event = event.unwrap_or(&null);
+
+    let mut fields = vec![];
+    // NOTE: Only GitHub Actions provides the event payload
+    let event_name = ci_info
+        .github()
+        .map_or("", |github_info| github_info.github_event_name.as_str());
+    match event_name {
+        "pull_request" | "pull_request_target" => {
+            let pull_request = &event["pull_request"];
+            if let (Some(number), Some(title), Some(url)) = (
+                pull_request["number"].as_u64(),
+                pull_request["title"].as_str(),
+                pull_request["html_url"].as_str(),
+            ) {
+                fields.push(md!(format!(
+                    "🔀*Pull Request:*\n<{url}|#{number} {}>",
+                    escape_mrkdwn(title)
+                )));
+            }
+            if let (Some(head), Some(base)) = (
+                pull_request["head"]["ref"].as_str(),
+                pull_request["base"]["ref"].as_str(),
+            ) {
+                fields.push(md!(format!(
+                    "🌿*Branch:*\n{} → {}",
+                    escape_mrkdwn(head),
+                    escape_mrkdwn(base)
+                )));
+            }
+        }
+        "release" => {
+            let release = &event["release"];
+            if let Some(url) = release["html_url"].as_str() {
+                let name = release["name"]
+                    .as_str()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(ci_info.ref_name());
+                fields.push(md!(format!(
+                    "🎉*Release:*\n<{url}|{}>",
+                    escape_mrkdwn(name)
+                )));
+            }
+            fields.extend(build_ref_field(ci_info, true));
+        }
+        "push" => {
+            let is_tag = event["ref"]
+                .as_str()
+                .is_some_and(|r| r.starts_with("refs/tags/"));
+            fields.extend(build_ref_field(ci_info, is_tag));
+            if let (Some(message), Some(url)) = (
+                event["head_commit"]["message"].as_str(),
+                event["head_commit"]["url"].as_str(),
+            ) {
+                let summary = message.lines().next().unwrap_or_default();
+                fields.push(md!(format!(
+                    "📝*Head Commit:*\n<{url}|{}>",
+                    escape_mrkdwn(summary)
+                )));
+            }
+        }
+        "workflow_dispatch" => {
+            fields.extend(build_ref_field(ci_info, false));
+            if let Some(inputs) = event["inputs"].as_object().filter(|i| !i.is_empty()) {
+                let inputs = inputs
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = match value {
+                            Value::String(v) => v.clone(),
+                            _ => value.to_string(),
+                        };
+                        format!("{}: `{}`", escape_mrkdwn(name), escape_mrkdwn(&value))
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                fields.push(md!(format!("⚙️*Inputs:*\n{inputs}")));
+            }
+        }
+        _ => fields.extend(build_ref_field(ci_info, false)),
+    }
+
+    if !ci_info.sha().is_empty() {
+        let sha = match ci_info.commit_url(ci_info.sha()) {
+            Some(url) => format!("<{url}|{}>", ci_info.short_sha()),
+            None => ci_info.short_sha().into(),
+        };
+        fields.push(md!(format!("🔖*Commit:*\n{sha}")));
+    }
+
+    fields
+}
+
+fn build_ref_field(ci_info: &dyn CiInfo, is_tag: bool) -> Option<SlackBlockText> {
+    if ci_info.ref_name().is_empty() {
+        return None;
+    }
+    let name = if is_tag {
+        "🏷️*Tag:*"
+    } else {
+        "🌿*Branch:*"
+    };
+    Some(md!(format!(
+        "{name}\n{}",
+        escape_mrkdwn(ci_info.ref_name())
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+    use rstest::rstest;
+    use serde_json::json;
+
+    const COMMIT: &str = "🔖*Commit:*\n<https://github.com/octocat/Hello-World/commit/ffac537e6cbbf934b08745a378932722df287a53|ffac537>";
+
+    #[rstest]
+    #[case(
+        "pull_request",
+        "feature",
+        json!({
+            "pull_request": {
+                "number": 12,
+                "title": "Add <payments> & refunds",
+                "html_url": "https://github.com/octocat/Hello-World/pull/12",
+                "head": { "ref": "feature" },
+                "base": { "ref": "main" },
+            }
+        }),
+        vec![
+            "🔀*Pull Request:*\n<https://github.com/octocat/Hello-World/pull/12|#12 Add &lt;payments&gt; &amp; refunds>",
+            "🌿*Branch:*\nfeature → main",
+        ]
+    )]
+    #[case(
+        "release",
+        "v1.42.0",
+        json!({
+            "release": {
+                "name": "Payments v1.42",
+                "html_url": "https://github.com/octocat/Hello-World/releases/tag/v1.42.0",
+            }
+        }),
+        vec![
+            "🎉*Release:*\n<https://github.com/octocat/Hello-World/releases/tag/v1.42.0|Payments v1.42>",
+            "🏷️*Tag:*\nv1.42.0",
+        ]
+    )]
+    #[case(
+        "push",
+        "main",
+        json!({
+            "ref": "refs/heads/main",
+            "head_commit": {
+                "message": "Fix rounding\n\nDetails",
+                "url": "https://github.com/octocat/Hello-World/commit/ffac537",
+            }
+        }),
+        vec![
+            "🌿*Branch:*\nmain",
+            "📝*Head Commit:*\n<https://github.com/octocat/Hello-World/commit/ffac537|Fix rounding>",
+        ]
+    )]
+    #[case(
+        "push",
+        "v1.42.0",
+        json!({ "ref": "refs/tags/v1.42.0" }),
+        vec!["🏷️*Tag:*\nv1.42.0"]
+    )]
+    #[case(
+        "workflow_dispatch",
+        "main",
+        json!({ "inputs": { "environment": "prod-eu", "dry_run": false } }),
+        vec![
+            "🌿*Branch:*\nmain",
+            "⚙️*Inputs:*\ndry_run: `false`\nenvironment: `prod-eu`",
+        ]
+    )]
+    #[case("schedule", "main", json!({}), vec!["🌿*Branch:*\nmain"])]
+    fn test_build_event_fields(
+        #[case] event_name: &str,
+        #[case] ref_name: &str,
+        #[case] event: Value,
+        #[case] expected: Vec<&str>,
+    ) {
+        let github_info = GitHubInfo {
+            github_event_name: event_name.into(),
+            github_ref_name: ref_name.into(),
+            ..GitHubInfo::for_test()
+        };
+
+        let actual = build_event_fields(&github_info, Some(&event));
+
+        let mut expected: Vec<SlackBlockText> = expected.into_iter().map(|v| md!(v)).collect();
+        expected.push(md!(COMMIT));
+        assert_eq!(actual, expected);
+    }
+}
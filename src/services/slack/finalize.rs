@@ -0,0 +1,221 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, bail};
+use slack_morphism::prelude::*;
+use tracing::{info, warn};
+
+use super::thread::SlackThread;
+use super::update::append_status_to_message;
+use super::{PostedMessage, client, post_message};
+use crate::services::github::file_commands;
+use crate::services::github::finalize_inputs::FinalizeInputs;
+use crate::services::github::github_api::{self, JobConclusion, JobResult};
+use crate::services::github::github_info::GitHubInfo;
+
+// Set in the main step so that the same binary knows it runs as the post step
+pub const STATE_IS_POST: &str = "is_post";
+const STATE_MESSAGES: &str = "messages";
+
+// Saves the copies of the approval which the post step reports the result of the job to
+pub fn save_messages(messages: &[PostedMessage]) -> Result<()> {
+    file_commands::save_state(&[(STATE_MESSAGES, encode_messages(messages))])
+}
+
+// Appends the result of the job like "✅Job succeeded in 4m 12s" to the approval
+pub async fn handle_slack_finalize(
+    github_info: &GitHubInfo,
+    finalize_inputs: &FinalizeInputs,
+) -> Result<()> {
+    if !finalize_inputs.post_job_status {
+        return Ok(());
+    }
+    // NOTE: Nothing is saved when the approval was rejected, cancelled or timed out
+    let Some(messages) = file_commands::get_state(STATE_MESSAGES) else {
+        info!("No approval to report the result of the job to");
+        return Ok(());
+    };
+    let messages = decode_messages(&messages)?;
+
+    // NOTE: The duration is of the whole job, which only the API knows the start of
+    let job_result = match &finalize_inputs.github_token {
+        Some(token) => github_api::fetch_job_result(github_info, token, &finalize_inputs.network)
+            .await
+            .inspect_err(|e| warn!("{:?}", e))
+            .ok(),
+        None => None,
+    };
+    let status = match job_result {
+        Some(JobResult {
+            conclusion,
+            started_at,
+        }) => build_job_status(
+            Some(conclusion),
+            started_at.and_then(|started_at| SystemTime::now().duration_since(started_at).ok()),
+        ),
+        None => build_job_status(None, None),
+    };
+
+    let client = client::new_client(&finalize_inputs.network)?;
+    let token = SlackApiToken::new(finalize_inputs.bot_token.clone());
+    let session = client.open_session(&token);
+
+    for message in &messages {
+        // NOTE: Replies in a thread cannot be fetched from the channel history
+        if message.thread.is_none() {
+            match append_status_to_message(&session, &message.channel_id, &message.ts, &status)
+                .await
+            {
+                Ok(()) => continue,
+                Err(e) => warn!("{:?}", e),
+            }
+        }
+        let content = SlackMessageContent::new().with_text(status.clone());
+        let thread = SlackThread {
+            ts: message.thread_ts().clone(),
+            reply_broadcast: false,
+        };
+        if let Err(e) = post_message(&session, &message.channel_id, content, Some(&thread)).await {
+            warn!("{:?}", e);
+        }
+    }
+
+    info!("Result of the job reported: {}", status);
+
+    Ok(())
+}
+
+fn build_job_status(conclusion: Option<JobConclusion>, elapsed: Option<Duration>) -> String {
+    let elapsed = elapsed.map(format_duration);
+    match (conclusion, elapsed) {
+        (Some(JobConclusion::Success), Some(elapsed)) => format!("✅Job succeeded in {elapsed}"),
+        (Some(JobConclusion::Success), None) => "✅Job succeeded".into(),
+        (Some(JobConclusion::Failure), Some(elapsed)) => format!("❌Job failed after {elapsed}"),
+        (Some(JobConclusion::Failure), None) => "❌Job failed".into(),
+        (Some(JobConclusion::Cancelled), Some(elapsed)) => {
+            format!("🚫Job cancelled after {elapsed}")
+        }
+        (Some(JobConclusion::Cancelled), None) => "🚫Job cancelled".into(),
+        (None, Some(elapsed)) => format!("🏁Job finished in {elapsed}"),
+        (None, None) => "🏁Job finished".into(),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, _) => format!("{h}h {m}m"),
+    }
+}
+
+// Each copy is `channel_id/ts` or `channel_id/ts/thread_ts` joined with commas
+fn encode_messages(messages: &[PostedMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| match &message.thread {
+            Some(thread) => format!("{}/{}/{}", message.channel_id, message.ts, thread.ts),
+            None => format!("{}/{}", message.channel_id, message.ts),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn decode_messages(v: &str) -> Result<Vec<PostedMessage>> {
+    v.split(',')
+        .map(|message| {
+            let parts: Vec<&str> = message.split('/').collect();
+            let (channel_id, ts, thread_ts) = match parts.as_slice() {
+                [channel_id, ts] => (channel_id, ts, None),
+                [channel_id, ts, thread_ts] => (channel_id, ts, Some(thread_ts)),
+                _ => bail!("Invalid message in state: {message}"),
+            };
+
+            Ok(PostedMessage {
+                channel_id: channel_id.to_string().into(),
+                ts: ts.to_string().into(),
+                thread: thread_ts.map(|ts| SlackThread {
+                    ts: ts.to_string().into(),
+                    reply_broadcast: false,
+                }),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Duration::from_secs(12), "12s")]
+    #[case(Duration::from_secs(252), "4m 12s")]
+    #[case(Duration::from_secs(3780), "1h 3m")]
+    fn test_format_duration(#[case] duration: Duration, #[case] expected: &str) {
+        assert_eq!(format_duration(duration), expected);
+    }
+
+    #[rstest]
+    #[case(
+        Some(JobConclusion::Success),
+        Some(Duration::from_secs(252)),
+        "✅Job succeeded in 4m 12s"
+    )]
+    #[case(
+        Some(JobConclusion::Failure),
+        Some(Duration::from_secs(12)),
+        "❌Job failed after 12s"
+    )]
+    #[case(Some(JobConclusion::Cancelled), None, "🚫Job cancelled")]
+    #[case(None, Some(Duration::from_secs(12)), "🏁Job finished in 12s")]
+    fn test_build_job_status(
+        #[case] conclusion: Option<JobConclusion>,
+        #[case] elapsed: Option<Duration>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(build_job_status(conclusion, elapsed), expected);
+    }
+
+    #[test]
+    fn should_encode_and_decode_messages() {
+        let messages = vec![
+            PostedMessage {
+                channel_id: "C1234567890".into(),
+                ts: "1700000000.000100".into(),
+                thread: None,
+            },
+            PostedMessage {
+                channel_id: "C0987654321".into(),
+                ts: "1700000000.000200".into(),
+                thread: Some(SlackThread {
+                    ts: "1690000000.000100".into(),
+                    reply_broadcast: true,
+                }),
+            },
+        ];
+
+        let encoded = encode_messages(&messages);
+        assert_eq!(
+            encoded,
+            "C1234567890/1700000000.000100,C0987654321/1700000000.000200/1690000000.000100"
+        );
+
+        let decoded = decode_messages(&encoded).unwrap();
+        assert_eq!(decoded[0].channel_id, messages[0].channel_id);
+        assert_eq!(decoded[0].thread, None);
+        assert_eq!(
+            decoded[1].thread.as_ref().map(|thread| &thread.ts),
+            Some(&"1690000000.000100".into())
+        );
+    }
+
+    #[test]
+    fn should_fail_to_decode_invalid_messages() {
+        let actual = decode_messages("C1234567890").map_err(|e| e.to_string());
+        assert_eq!(
+            actual.map(|_| ()),
+            Err("Invalid message in state: C1234567890".into())
+        );
+    }
+}
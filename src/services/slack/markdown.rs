@@ -0,0 +1,311 @@
+use slack_morphism::prelude::*;
+
+use super::block_limits::MAX_SECTION_TEXT;
+use super::content::escape_mrkdwn;
+
+const CODE_FENCE: &str = "```";
+
+// Converts GitHub-flavored Markdown into Slack mrkdwn line by line
+// https://docs.slack.dev/messaging/formatting-message-text
+pub fn markdown_to_mrkdwn(markdown: &str) -> String {
+    let mut lines = vec![];
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with(CODE_FENCE) || trimmed.starts_with("~~~") {
+            // NOTE: Slack does not highlight code, so the language is dropped
+            in_code = !in_code;
+            lines.push(CODE_FENCE.into());
+            continue;
+        }
+        if in_code {
+            lines.push(escape_mrkdwn(line));
+            continue;
+        }
+
+        lines.push(convert_line(line));
+    }
+
+    lines.join("\n")
+}
+
+fn convert_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let level = (line.len() - trimmed.len()) / 2;
+
+    // Headings
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let text = trimmed[hashes..].trim().trim_end_matches('#').trim_end();
+        return format!("*{}*", convert_inline(text));
+    }
+
+    // Horizontal rules
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|c| compact.chars().all(|v| v.to_string() == *c))
+    {
+        return "──────────".into();
+    }
+
+    // Block quotes
+    if let Some(text) = trimmed.strip_prefix('>') {
+        return format!(">{}", convert_inline(text));
+    }
+
+    // Unordered lists including task lists
+    if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+    {
+        let bullet = if level == 0 { "•" } else { "◦" };
+        let item = if let Some(item) = item.strip_prefix("[ ] ") {
+            format!("☐ {}", convert_inline(item))
+        } else if let Some(item) = item
+            .strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+        {
+            format!("☑ {}", convert_inline(item))
+        } else {
+            convert_inline(item)
+        };
+        return format!("{}{bullet} {item}", "    ".repeat(level));
+    }
+
+    // Ordered lists keep their numbers
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && trimmed[digits..].starts_with(". ") {
+        return format!(
+            "{}{}. {}",
+            "    ".repeat(level),
+            &trimmed[..digits],
+            convert_inline(&trimmed[digits + 2..])
+        );
+    }
+
+    convert_inline(line)
+}
+
+// Emphasis markers of Markdown and their mrkdwn counterparts, longest first
+const EMPHASES: [(&str, &str); 4] = [("**", "*"), ("__", "*"), ("~~", "~"), ("*", "_")];
+
+fn convert_inline(text: &str) -> String {
+    let mut converted = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // Code spans are kept as they are
+        if c == '`'
+            && let Some(end) = rest[1..].find('`')
+        {
+            converted.push_str(&format!("`{}`", escape_mrkdwn(&rest[1..end + 1])));
+            rest = &rest[end + 2..];
+            continue;
+        }
+
+        // Images are linked since they cannot be shown inline
+        let link_start = if rest.starts_with("![") { 1 } else { 0 };
+        if rest[link_start..].starts_with('[')
+            && let Some((label, url, length)) = parse_link(&rest[link_start..])
+        {
+            converted.push_str(&format!(
+                "<{}|{}>",
+                escape_mrkdwn(url),
+                convert_inline(label)
+            ));
+            rest = &rest[link_start + length..];
+            continue;
+        }
+
+        // Autolinks like <https://example.com>
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && (rest[1..end].starts_with("http://") || rest[1..end].starts_with("https://"))
+            && !rest[1..end].contains(' ')
+        {
+            converted.push_str(&format!("<{}>", escape_mrkdwn(&rest[1..end])));
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        if let Some((marker, replacement, inner)) = find_emphasis(rest) {
+            converted.push_str(&format!(
+                "{replacement}{}{replacement}",
+                convert_inline(inner)
+            ));
+            rest = &rest[marker.len() * 2 + inner.len()..];
+            continue;
+        }
+
+        converted.push_str(&escape_mrkdwn(&c.to_string()));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    converted
+}
+
+// Finds emphasis at the start and returns the marker, its replacement and the emphasized text
+fn find_emphasis(text: &str) -> Option<(&str, &str, &str)> {
+    EMPHASES.iter().find_map(|(marker, replacement)| {
+        let after = text.strip_prefix(marker)?;
+        let inner = &after[..after.find(marker)?];
+        // NOTE: `2 * 3 * 4` is not emphasis
+        if inner.is_empty() || inner.starts_with(' ') || inner.ends_with(' ') {
+            return None;
+        }
+
+        Some((*marker, *replacement, inner))
+    })
+}
+
+// Parses `[label](url)` at the start and returns the label, the URL and the consumed length
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = text[label_end + 2..].find(')')? + label_end + 2;
+    let label = &text[1..label_end];
+    let url = &text[label_end + 2..url_end];
+    if label.contains('[') || url.is_empty() || url.contains(' ') {
+        return None;
+    }
+
+    Some((label, url, url_end + 1))
+}
+
+// Splits mrkdwn into sections within the text limit
+// Lines are not split unless they are too long, and code blocks are closed and reopened across sections
+pub fn build_text_sections(text: &str) -> Vec<SlackBlock> {
+    split_mrkdwn(text, MAX_SECTION_TEXT)
+        .into_iter()
+        .map(|chunk| SlackSectionBlock::new().with_text(md!(chunk)).into())
+        .collect()
+}
+
+fn split_mrkdwn(text: &str, max: usize) -> Vec<String> {
+    // NOTE: Room for closing and reopening a code block
+    let fence_length = CODE_FENCE.len() + 1;
+    let max_line = max - fence_length * 2;
+
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+    let mut chunk_length = 0;
+    let mut in_code = false;
+
+    for line in text.lines() {
+        let opens_or_closes = line.matches(CODE_FENCE).count() % 2 == 1;
+        for piece in split_line(line, max_line) {
+            let length = piece.chars().count() + 1;
+            let closing = if in_code { fence_length } else { 0 };
+            if chunk_length > 0 && chunk_length + length + closing > max {
+                if in_code {
+                    chunk.push_str(CODE_FENCE);
+                }
+                chunks.push(std::mem::take(&mut chunk).trim_end().to_string());
+                chunk_length = 0;
+                if in_code {
+                    chunk.push_str(&format!("{CODE_FENCE}\n"));
+                    chunk_length = fence_length;
+                }
+            }
+            chunk.push_str(&piece);
+            chunk.push('\n');
+            chunk_length += length;
+        }
+        if opens_or_closes {
+            in_code = !in_code;
+        }
+    }
+    let chunk = chunk.trim_end();
+    if !chunk.is_empty() {
+        chunks.push(chunk.into());
+    }
+
+    chunks
+}
+
+fn split_line(line: &str, max: usize) -> Vec<String> {
+    if line.chars().count() <= max {
+        return vec![line.into()];
+    }
+
+    line.chars()
+        .collect::<Vec<char>>()
+        .chunks(max)
+        .map(|chars| chars.iter().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("# Release v1.42", "*Release v1.42*")]
+    #[case("### Notes ###", "*Notes*")]
+    #[case(
+        "**Bold**, __bold__, *italic* and ~~strike~~",
+        "*Bold*, *bold*, _italic_ and ~strike~"
+    )]
+    #[case("2 * 3 * 4 and snake_case_name", "2 * 3 * 4 and snake_case_name")]
+    #[case(
+        "See [the **docs**](https://example.com/a?b=1&c=2)",
+        "See <https://example.com/a?b=1&amp;c=2|the *docs*>"
+    )]
+    #[case(
+        "![diagram](https://example.com/d.png)",
+        "<https://example.com/d.png|diagram>"
+    )]
+    #[case("Visit <https://example.com>", "Visit <https://example.com>")]
+    #[case("`a < b && *c*` < d", "`a &lt; b &amp;&amp; *c*` &lt; d")]
+    #[case(
+        "- item\n  * nested\n- [ ] todo\n- [x] done",
+        "• item\n    ◦ nested\n• ☐ todo\n• ☑ done"
+    )]
+    #[case("1. first\n2. **second**", "1. first\n2. *second*")]
+    #[case("> quoted *text*", "> quoted _text_")]
+    #[case("---", "──────────")]
+    #[case("```rust\nlet a = **b** < c;\n```", "```\nlet a = **b** &lt; c;\n```")]
+    fn test_markdown_to_mrkdwn(#[case] markdown: &str, #[case] expected: &str) {
+        assert_eq!(markdown_to_mrkdwn(markdown), expected);
+    }
+
+    #[test]
+    fn should_split_at_lines() {
+        let text = ["a".repeat(10), "b".repeat(10), "c".repeat(10)].join("\n");
+
+        let actual = split_mrkdwn(&text, 25);
+
+        assert_eq!(
+            actual,
+            vec![["a".repeat(10), "b".repeat(10)].join("\n"), "c".repeat(10)]
+        );
+    }
+
+    #[test]
+    fn should_reopen_code_block_across_sections() {
+        let text = ["```", &"a".repeat(10), &"b".repeat(10), "```"].join("\n");
+
+        let actual = split_mrkdwn(&text, 25);
+
+        assert_eq!(
+            actual,
+            vec![
+                format!("```\n{}\n```", "a".repeat(10)),
+                format!("```\n{}\n```", "b".repeat(10)),
+            ]
+        );
+        assert!(actual.iter().all(|chunk| chunk.chars().count() <= 25));
+    }
+
+    #[test]
+    fn should_split_long_line() {
+        let text = "a".repeat(MAX_SECTION_TEXT * 2);
+
+        let actual = build_text_sections(&text);
+
+        assert_eq!(actual.len(), 3);
+    }
+}
@@ -0,0 +1,526 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use slack_morphism::prelude::*;
+use tracing::{info, warn};
+
+use crate::services::chat::{self, Authorization, Decision};
+use crate::services::ci::CiInfo;
+use crate::services::git;
+use crate::services::github::file_commands;
+use crate::services::github::github_inputs::{DestroyPolicy, GitHubInputs, Mode};
+use crate::services::template::TemplateContext;
+use crate::services::termination::Termination;
+use crate::services::terraform;
+use approval_metadata::ApprovalMetadata;
+use thread::SlackThread;
+use transport::SlackTransport;
+
+mod api_error;
+mod approval_metadata;
+mod attachment;
+mod block_limits;
+mod block_template;
+mod check;
+mod client;
+mod commit_list;
+mod content;
+mod direct_message;
+mod event_fields;
+mod finalize;
+mod markdown;
+mod retry;
+mod socket_mode;
+mod supersede;
+mod terraform_summary;
+mod thread;
+mod transport;
+mod update;
+
+pub use check::handle_slack_check;
+pub use finalize::{STATE_IS_POST, handle_slack_finalize};
+pub use update::handle_slack_update;
+
+const SLACK_APPROVAL_APPROVE_ACTION_ID: &str = "slack-approval-approve";
+const SLACK_APPROVAL_REJECT_ACTION_ID: &str = "slack-approval-reject";
+
+// Number of messages to look through in the channel history
+const HISTORY_LIMIT: u16 = 100;
+
+pub async fn handle_slack_approval(
+    ci_info: &dyn CiInfo,
+    github_inputs: &GitHubInputs,
+    mut termination: Termination,
+) -> Result<()> {
+    let client = Arc::new(client::new_client(&github_inputs.network)?);
+    let token = SlackApiToken::new(github_inputs.bot_token.clone().into());
+    let session = client.open_session(&token);
+
+    let attachments = attachment::read_attachments(&github_inputs.attachments)?;
+    let commits = github_inputs
+        .commits_base
+        .as_deref()
+        .map(|base| list_commits(ci_info, base))
+        .transpose()?;
+
+    let plan = github_inputs
+        .terraform_plan
+        .as_deref()
+        .map(terraform::read_plan)
+        .transpose()?;
+    let plan_destroys = plan.as_ref().is_some_and(|plan| plan.has_destroys());
+    if plan_destroys && github_inputs.terraform_destroy == DestroyPolicy::Deny {
+        bail!("Terraform plan destroys resources, and `terraform-destroy` is `deny`");
+    }
+
+    let metadata = ApprovalMetadata::pending(ci_info);
+    let template_context = TemplateContext::new(ci_info)?
+        .with_escape(content::escape_mrkdwn)
+        .with_value("approval.mentions", content::build_header(github_inputs));
+    let approval_content = content::build_content(
+        github_inputs,
+        ci_info,
+        &template_context,
+        commits.as_ref(),
+        plan.as_ref(),
+    )
+    .with_context(|| "Failed to build message")?;
+    // NOTE: Only approvals have metadata so that they are found by `supersede-pending`
+    let mut content = match github_inputs.mode {
+        Mode::Approval => approval_content
+            .message
+            .with_metadata(metadata.to_slack_metadata()),
+        Mode::Notify => approval_content.message,
+    };
+    if let (Some(plan), Some(blocks)) = (&plan, content.blocks.as_mut())
+        && plan_destroys
+        && github_inputs.terraform_destroy == DestroyPolicy::Confirm
+    {
+        terraform_summary::require_destroy_confirmation(blocks, plan);
+    }
+    block_limits::validate_blocks(content.blocks.as_deref().unwrap_or_default())?;
+
+    let mut transport = SlackTransport::new(
+        client.clone(),
+        token.clone(),
+        github_inputs,
+        ci_info,
+        content.clone(),
+        metadata,
+    );
+    let authorization = Authorization::collect(
+        &transport,
+        &github_inputs.authorized_users,
+        &github_inputs.authorized_groups,
+    )
+    .await
+    .with_context(|| "Failed to collect authorized users")?;
+    if github_inputs.post_to.direct_message && !authorization.should_authorize() {
+        bail!(
+            "Direct messages are sent to authorized users. Specify `authorized-users` or `authorized-groups`"
+        );
+    }
+
+    // NOTE: Connected before posting like the other backends, so that no click is missed and a failure posts nothing
+    if github_inputs.mode == Mode::Approval {
+        let Some(app_token) = &github_inputs.app_token else {
+            bail!("Input 'app-token' is required");
+        };
+        transport
+            .listen(&app_token.clone().into(), &github_inputs.network)
+            .await?;
+    }
+
+    if github_inputs.post_to.channel {
+        chat::post_all(&mut transport, &github_inputs.channel_ids).await?;
+    }
+    if github_inputs.post_to.direct_message {
+        let user_ids: Vec<SlackUserId> = authorization
+            .users()
+            .iter()
+            .map(|user_id| SlackUserId::new(user_id.clone()))
+            .collect();
+        transport
+            .messages
+            .extend(direct_message::post_direct_messages(&session, &user_ids, &content).await);
+    }
+    let messages = transport.messages.clone();
+    if messages.is_empty() {
+        bail!("Approval was not posted to anywhere");
+    }
+    if let Err(e) = set_outputs(&session, &messages[0]).await {
+        chat::cancel(&transport).await;
+        return Err(e);
+    }
+    for blocks in approval_content.overflow.chunks(block_limits::MAX_BLOCKS) {
+        let content = SlackMessageContent::new().with_blocks(blocks.to_vec());
+        post_replies(&session, &messages, content).await;
+    }
+    if let Some(commits) = &commits {
+        let content = commit_list::build_commit_list_content(ci_info, commits);
+        post_replies(&session, &messages, content).await;
+    }
+    if !attachments.is_empty()
+        && let Err(e) = attachment::upload_attachments(&session, &messages, &attachments).await
+    {
+        chat::cancel(&transport).await;
+        return Err(e);
+    }
+    if github_inputs.mode == Mode::Notify {
+        finalize::save_messages(&messages)?;
+        info!("Posted without waiting for approval");
+        return Ok(());
+    }
+
+    let decision = chat::wait_for_decision(&mut transport, &authorization, &mut termination).await;
+    transport.shutdown();
+
+    match decision? {
+        Decision::Approved => finalize::save_messages(&messages),
+        Decision::Rejected => bail!("Approval was rejected"),
+    }
+}
+
+// A copy of the approval message
+#[derive(Debug, Clone)]
+struct PostedMessage {
+    channel_id: SlackChannelId,
+    ts: SlackTs,
+    thread: Option<SlackThread>,
+}
+
+impl PostedMessage {
+    // Replies go into the thread the approval is in, or start a thread under the approval
+    fn thread_ts(&self) -> &SlackTs {
+        self.thread.as_ref().map_or(&self.ts, |thread| &thread.ts)
+    }
+}
+
+// Later steps can update the approval with these outputs
+// NOTE: The first copy is used when the approval is posted to multiple places
+// NOTE: `permalink` is empty when it cannot be fetched, as the others are enough to update the approval
+async fn set_outputs<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    message: &PostedMessage,
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiChatGetPermalinkRequest::new(message.channel_id.clone(), message.ts.clone());
+    let permalink =
+        match retry::with_retry("chat.getPermalink", || session.chat_get_permalink(&req)).await {
+            Ok(res) => res.permalink.to_string(),
+            Err(e) => {
+                warn!(
+                    "Failed to get permalink. channel_id: {}, ts: {}: {e:#}",
+                    message.channel_id, message.ts
+                );
+                String::new()
+            }
+        };
+
+    file_commands::set_outputs(&[
+        ("channel-id", message.channel_id.to_string()),
+        ("ts", message.ts.to_string()),
+        ("thread-ts", message.thread_ts().to_string()),
+        ("permalink", permalink),
+    ])
+}
+
+fn list_commits(ci_info: &dyn CiInfo, base: &str) -> Result<git::CommitRange> {
+    let workspace = ci_info.workspace().unwrap_or(".");
+    // NOTE: Local runs have no commit, so the checked out one is the head
+    let head = Some(ci_info.sha())
+        .filter(|sha| !sha.is_empty())
+        .unwrap_or("HEAD");
+    git::list_commits(Path::new(workspace), head, base, commit_list::MAX_COMMITS)
+        .with_context(|| format!("Failed to list commits since {base}"))
+}
+
+// Replies to every copy with details of the approval
+// NOTE: The approval works without the details, so failures are only logged
+async fn post_replies<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    messages: &[PostedMessage],
+    content: SlackMessageContent,
+) where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    for message in messages {
+        let thread = SlackThread {
+            ts: message.thread_ts().clone(),
+            reply_broadcast: false,
+        };
+        if let Err(e) =
+            post_message(session, &message.channel_id, content.clone(), Some(&thread)).await
+        {
+            warn!("Failed to post reply: {:?}", e);
+        }
+    }
+}
+
+// Removes the buttons so that no one can decide anymore and shows the result instead
+fn replace_actions_block(blocks: &[SlackBlock], text: &str) -> Vec<SlackBlock> {
+    let mut response_blocks: Vec<SlackBlock> = blocks
+        .iter()
+        .filter(|block| !matches!(block, SlackBlock::Actions(_)))
+        .cloned()
+        .collect();
+    response_blocks.push(SlackBlock::Section(
+        SlackSectionBlock::new().with_text(md!(text)),
+    ));
+
+    response_blocks
+}
+
+async fn fetch_user_ids_from_groups<SCHC>(
+    session: &SlackClientSession<'_, SCHC>,
+    authorized_groups: &Vec<SlackUserGroupId>,
+) -> Result<Vec<SlackUserId>>
+where
+    SCHC: SlackClientHttpConnector + Send,
+{
+    let mut user_ids = vec![];
+
+    for group in authorized_groups {
+        let req = SlackApiUserGroupsUsersListRequest::new(group.clone());
+        let res = retry::with_retry("usergroups.users.list", || {
+            session.usergroups_users_list(&req)
+        })
+        .await
+        .with_context(|| format!("Failed to fetch user IDs from group. group: {group}"))?;
+        user_ids.extend(res.users);
+    }
+
+    info!("User IDs from groups: {:?}", user_ids);
+
+    Ok(user_ids)
+}
+
+async fn post_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    content: SlackMessageContent,
+    thread: Option<&SlackThread>,
+) -> Result<SlackTs>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    try_post_message(session, channel_id, content, thread)
+        .await
+        .map_err(|e| {
+            api_error::with_diagnosis(
+                e,
+                format!("Failed to post message. channel_id: {channel_id}"),
+            )
+        })
+}
+
+// Joins the channel and posts again when the bot is not in it
+// NOTE: Only public channels can be joined, which needs `channels:join`
+async fn post_or_join<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    content: SlackMessageContent,
+    thread: Option<&SlackThread>,
+    join_channel: bool,
+) -> Result<SlackTs>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    match try_post_message(session, channel_id, content.clone(), thread).await {
+        Err(e) if join_channel && api_error::api_error_code(&e) == Some("not_in_channel") => {
+            info!("Joining channel: {channel_id}");
+            let req = SlackApiConversationsJoinRequest::new(channel_id.clone());
+            retry::with_retry("conversations.join", || session.conversations_join(&req))
+                .await
+                .map_err(|e| {
+                    api_error::with_diagnosis(
+                        e,
+                        format!("Failed to join channel. channel_id: {channel_id}"),
+                    )
+                })?;
+            post_message(session, channel_id, content, thread).await
+        }
+        res => res.map_err(|e| {
+            api_error::with_diagnosis(
+                e,
+                format!("Failed to post message. channel_id: {channel_id}"),
+            )
+        }),
+    }
+}
+
+async fn try_post_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    content: SlackMessageContent,
+    thread: Option<&SlackThread>,
+) -> ClientResult<SlackTs>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiChatPostMessageRequest::new(channel_id.clone(), content)
+        .opt_thread_ts(thread.map(|thread| thread.ts.clone()))
+        .opt_reply_broadcast(thread.map(|thread| thread.reply_broadcast));
+    let res =
+        retry::with_retry_once("chat.postMessage", || session.chat_post_message(&req)).await?;
+
+    Ok(res.ts)
+}
+
+async fn fetch_history<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    latest: Option<&SlackTs>,
+) -> Result<Vec<SlackHistoryMessage>>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiConversationsHistoryRequest::new()
+        .with_channel(channel_id.clone())
+        .opt_latest(latest.cloned())
+        .with_inclusive(false)
+        .with_limit(HISTORY_LIMIT)
+        .with_include_all_metadata(true);
+    let res = retry::with_retry("conversations.history", || session.conversations_history(&req))
+        .await
+        .with_context(|| {
+            format!("Failed to fetch conversation history. Have you added `channels:history` or `groups:history` scope? channel_id: {channel_id}")
+        })?;
+
+    Ok(res.messages)
+}
+
+// Tries to update every copy even if some of them fail
+async fn update_messages<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    messages: &[PostedMessage],
+    content: SlackMessageContent,
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let mut result = Ok(());
+    for message in messages {
+        if let Err(e) =
+            update_message(session, &message.channel_id, content.clone(), &message.ts).await
+        {
+            warn!("{:?}", e);
+            result = Err(e);
+        }
+    }
+
+    result
+}
+
+async fn update_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    content: SlackMessageContent,
+    ts: &SlackTs,
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiChatUpdateRequest::new(channel_id.clone(), content, ts.clone());
+    retry::with_retry("chat.update", || session.chat_update(&req))
+        .await
+        .with_context(
+            || format!("Failed to update message. channel_id: {channel_id}, ts: {ts}",),
+        )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rstest::rstest;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::services::config::Config;
+    use crate::services::fake_slack::{FakeSlack, FakeSlackOptions};
+    use crate::services::github::github_info::GitHubInfo;
+    use crate::services::github::github_inputs::read_github_inputs;
+
+    // Clicks once the approval is posted and the app is connected
+    async fn click(control_url: &str, button: &str, user: &str) {
+        let client = reqwest::Client::new();
+        for _ in 0..250 {
+            let res = client
+                .post(format!("{control_url}/click"))
+                .json(&json!({ "button": button, "user": user }))
+                .send()
+                .await
+                .unwrap();
+            if res.status().is_success() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("Approval was not posted");
+    }
+
+    #[rstest]
+    #[case::approve("approve", true, "Approved by <@U1234567890>")]
+    #[case::reject("reject", false, "Rejected by <@U1234567890>")]
+    #[tokio::test]
+    async fn should_decide_on_fake_slack(
+        #[case] button: &str,
+        #[case] expected: bool,
+        #[case] expected_text: &str,
+    ) {
+        let fake_slack = FakeSlack::start(&FakeSlackOptions {
+            host: "127.0.0.1".into(),
+            port: 0,
+            usergroups: vec![],
+        })
+        .await
+        .unwrap();
+        let api_url = fake_slack.api_url();
+        let control_url = api_url.replace("/api", "/_fake");
+        let github_inputs = read_github_inputs(&Config::from_values(&[
+            ("bot-token", "xoxb-bot-token"),
+            ("app-token", "xapp-app-token"),
+            ("channel-id", "C1234567890"),
+            ("authorized-users", "U1234567890"),
+            ("title", "Deploy"),
+            ("slack-api-url", &api_url),
+        ]))
+        .unwrap();
+        let github_info = GitHubInfo::for_test();
+        let termination = Termination::install(None).unwrap();
+
+        let (actual, _) = tokio::join!(
+            handle_slack_approval(&github_info, &github_inputs, termination),
+            async {
+                click(&control_url, button, "U0000000000").await;
+                click(&control_url, button, "U1234567890").await;
+            }
+        );
+
+        assert_eq!(actual.is_ok(), expected);
+        let messages: Value = reqwest::get(format!("{control_url}/messages"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let messages = messages["messages"].as_array().unwrap();
+        // The approval and the notice to the unauthorized user
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].to_string().contains(expected_text));
+        assert!(
+            messages[1]["text"]
+                .as_str()
+                .unwrap()
+                .contains("You are not authorized")
+        );
+
+        fake_slack.shutdown();
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::prelude::*;
+use tracing::warn;
+
+// Attempts including the first one
+const MAX_ATTEMPTS: u32 = 5;
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+// Error codes which Slack returns when it is the one to blame
+// https://api.slack.com/methods/chat.postMessage#errors
+const TRANSIENT_ERROR_CODES: &[&str] = &[
+    "internal_error",
+    "fatal_error",
+    "service_unavailable",
+    "request_timeout",
+];
+
+// Calls again on rate limits, 5xx and network errors
+pub(super) async fn with_retry<T, F, Fut>(method: &str, call: F) -> ClientResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    retry(method, call, |_| true).await
+}
+
+// Calls again only when Slack has not done what was asked, for calls like `chat.postMessage`
+// NOTE: A duplicate approval would keep buttons which no run listens to, which is worse than failing
+pub(super) async fn with_retry_once<T, F, Fut>(method: &str, call: F) -> ClientResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    retry(method, call, |e| !may_be_done(e)).await
+}
+
+async fn retry<T, F, Fut>(
+    method: &str,
+    mut call: F,
+    is_retryable: impl Fn(&SlackClientError) -> bool,
+) -> ClientResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if attempt < MAX_ATTEMPTS && is_retryable(&e) => {
+                let Some(delay) = retry_delay(&e, attempt, jitter()) else {
+                    return Err(e);
+                };
+                warn!(
+                    "Retrying {method} in {}ms ({attempt}/{}): {e}",
+                    delay.as_millis(),
+                    MAX_ATTEMPTS - 1
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+// None when calling again would fail in the same way
+// `jitter` is between 0 and 1 so that concurrent jobs do not retry at once
+fn retry_delay(e: &SlackClientError, attempt: u32, jitter: f64) -> Option<Duration> {
+    let backoff = || {
+        let delay = BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(MAX_DELAY);
+        // Between the half and the whole of the delay
+        delay.mul_f64(0.5 + jitter / 2.0)
+    };
+    match e {
+        // NOTE: Slack tells how long to wait, which is not shortened
+        SlackClientError::RateLimitError(e) => Some(e.retry_after.unwrap_or_else(backoff)),
+        SlackClientError::HttpError(e) if e.status_code.is_server_error() => Some(backoff()),
+        SlackClientError::HttpProtocolError(_) | SlackClientError::EndOfStream(_) => {
+            Some(backoff())
+        }
+        SlackClientError::ApiError(e) if TRANSIENT_ERROR_CODES.contains(&e.code.as_str()) => {
+            Some(backoff())
+        }
+        _ => None,
+    }
+}
+
+// Whether Slack may have done what was asked although the call failed
+// NOTE: The response may be lost after the request is delivered, and Slack says some errors may happen after success
+fn may_be_done(e: &SlackClientError) -> bool {
+    match e {
+        SlackClientError::RateLimitError(_) => false,
+        SlackClientError::HttpError(e) => e.status_code != 503,
+        SlackClientError::ApiError(e) => {
+            matches!(e.code.as_str(), "internal_error" | "fatal_error")
+        }
+        _ => true,
+    }
+}
+
+fn jitter() -> f64 {
+    // NOTE: Random enough for backoff without a dependency on `rand`
+    let random = RandomState::new().build_hasher().finish();
+    random as f64 / u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use slack_morphism::errors::{
+        SlackClientApiError, SlackClientHttpError, SlackClientHttpProtocolError,
+        SlackRateLimitError,
+    };
+
+    use super::*;
+
+    fn api_error(code: &str) -> SlackClientError {
+        SlackClientError::ApiError(SlackClientApiError::new(code.into()))
+    }
+
+    fn http_error(status: u16) -> SlackClientError {
+        SlackClientError::HttpError(SlackClientHttpError::new(status.try_into().unwrap()))
+    }
+
+    #[rstest]
+    #[case::retry_after(
+        SlackClientError::RateLimitError(SlackRateLimitError::new().with_retry_after(Duration::from_secs(42))),
+        1,
+        Some(Duration::from_secs(42))
+    )]
+    #[case::rate_limited(
+        SlackClientError::RateLimitError(SlackRateLimitError::new()),
+        1,
+        Some(Duration::from_secs(1))
+    )]
+    #[case::server_error(http_error(503), 3, Some(Duration::from_secs(4)))]
+    #[case::capped(http_error(500), 10, Some(MAX_DELAY))]
+    #[case::network(
+        SlackClientError::HttpProtocolError(SlackClientHttpProtocolError::new()),
+        2,
+        Some(Duration::from_secs(2))
+    )]
+    #[case::transient_code(api_error("internal_error"), 1, Some(Duration::from_secs(1)))]
+    #[case::client_error(http_error(400), 1, None)]
+    #[case::permanent_code(api_error("channel_not_found"), 1, None)]
+    fn should_decide_retry_delay(
+        #[case] e: SlackClientError,
+        #[case] attempt: u32,
+        #[case] expected: Option<Duration>,
+    ) {
+        assert_eq!(retry_delay(&e, attempt, 1.0), expected);
+    }
+
+    #[rstest]
+    #[case::rate_limited(SlackClientError::RateLimitError(SlackRateLimitError::new()), false)]
+    #[case::unavailable(http_error(503), false)]
+    #[case::request_timeout(api_error("request_timeout"), false)]
+    #[case::server_error(http_error(500), true)]
+    #[case::internal_error(api_error("internal_error"), true)]
+    #[case::network(
+        SlackClientError::HttpProtocolError(SlackClientHttpProtocolError::new()),
+        true
+    )]
+    fn test_may_be_done(#[case] e: SlackClientError, #[case] expected: bool) {
+        assert_eq!(may_be_done(&e), expected);
+    }
+
+    #[tokio::test]
+    async fn should_not_post_again_after_network_error() {
+        let mut calls = 0;
+
+        let actual: ClientResult<()> = with_retry_once("chat.postMessage", || {
+            calls += 1;
+            async {
+                Err(SlackClientError::HttpProtocolError(
+                    SlackClientHttpProtocolError::new(),
+                ))
+            }
+        })
+        .await;
+
+        assert!(actual.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn should_jitter_backoff() {
+        let e = http_error(502);
+
+        assert_eq!(retry_delay(&e, 2, 0.0), Some(Duration::from_secs(1)));
+        assert_eq!(retry_delay(&e, 2, 0.5), Some(Duration::from_millis(1500)));
+    }
+
+    #[tokio::test]
+    async fn should_retry_until_success() {
+        let mut calls = 0;
+
+        let actual = with_retry("chat.update", || {
+            calls += 1;
+            // NOTE: Slack asks to wait only a moment so that the test does not sleep
+            let res = if calls < 3 {
+                Err(SlackClientError::RateLimitError(
+                    SlackRateLimitError::new().with_retry_after(Duration::from_millis(1)),
+                ))
+            } else {
+                Ok(calls)
+            };
+            async move { res }
+        })
+        .await;
+
+        assert_eq!(actual.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_permanent_error() {
+        let mut calls = 0;
+
+        let actual: ClientResult<()> = with_retry("chat.update", || {
+            calls += 1;
+            async { Err(api_error("message_not_found")) }
+        })
+        .await;
+
+        assert!(actual.is_err());
+        assert_eq!(calls, 1);
+    }
+}
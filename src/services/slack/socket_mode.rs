@@ -0,0 +1,289 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use futures_util::{SinkExt, StreamExt};
+use rustls::ClientConfig;
+use serde::Deserialize;
+use serde_json::Value;
+use slack_morphism::prelude::*;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+use super::client::SlackHttpClient;
+use super::retry;
+use crate::services::network::{self, NetworkOptions};
+
+const HELLO_TIMEOUT: Duration = Duration::from_secs(30);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// https://docs.slack.dev/apis/events-api/using-socket-mode
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: String,
+    envelope_id: Option<String>,
+    payload: Option<Value>,
+}
+
+// Receives the button clicks over Socket Mode
+// NOTE: The listener of slack-morphism connects to Slack directly, so it cannot go through the proxy
+pub(super) struct SocketMode {
+    task: JoinHandle<()>,
+}
+
+impl SocketMode {
+    // Returns once Slack says hello, so that no click is missed after this
+    pub(super) async fn connect<S, F, Fut>(
+        client: Arc<SlackHttpClient>,
+        app_token: &SlackApiTokenValue,
+        network: &NetworkOptions,
+        state: Arc<S>,
+        handler: F,
+    ) -> Result<Self>
+    where
+        S: Send + Sync + 'static,
+        F: Fn(SlackInteractionEvent, Arc<SlackHttpClient>, Arc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let connection = Connection {
+            client,
+            token: SlackApiToken::new(app_token.clone()),
+            tls: network::tls_config(network)?,
+        };
+        let mut socket = connection.open().await.with_context(|| {
+            "Failed to connect to slack socket mode. Have you enabled socket mode in your slack app?"
+        })?;
+
+        let task = tokio::spawn(async move {
+            loop {
+                connection.serve(&mut socket, &state, &handler).await;
+                socket = connection.reopen().await;
+            }
+        });
+
+        Ok(Self { task })
+    }
+
+    pub(super) fn shutdown(self) {
+        self.task.abort();
+    }
+
+    // Opens a connection and closes it once Slack says hello
+    // NOTE: Nothing is acknowledged, so that Slack sends the clicks to the connection of a waiting approval again
+    pub(super) async fn probe(
+        client: Arc<SlackHttpClient>,
+        app_token: &SlackApiTokenValue,
+        network: &NetworkOptions,
+    ) -> Result<()> {
+        let connection = Connection {
+            client,
+            token: SlackApiToken::new(app_token.clone()),
+            tls: network::tls_config(network)?,
+        };
+        let mut socket = connection.open().await.with_context(|| {
+            "Failed to connect to slack socket mode. Have you enabled socket mode in your slack app?"
+        })?;
+        if let Err(e) = socket.close(None).await {
+            debug!("Failed to close socket mode connection: {e}");
+        }
+
+        Ok(())
+    }
+}
+
+struct Connection {
+    client: Arc<SlackHttpClient>,
+    token: SlackApiToken,
+    tls: Arc<ClientConfig>,
+}
+
+impl Connection {
+    async fn open(&self) -> Result<Socket> {
+        let session = self.client.open_session(&self.token);
+        let req = SlackApiAppsConnectionOpenRequest::new();
+        let res = retry::with_retry("apps.connections.open", || {
+            session.apps_connections_open(&req)
+        })
+        .await
+        .with_context(|| "Failed to open socket mode connection")?;
+        let url = res.url.0;
+        let Some(host) = url.host_str() else {
+            bail!("URL of socket mode has no host: {url}");
+        };
+
+        let stream = network::connect(host, url.port_or_known_default().unwrap_or(443)).await?;
+        let (mut socket, _) = tokio_tungstenite::client_async_tls_with_config(
+            url.as_str(),
+            stream,
+            None,
+            Some(Connector::Rustls(self.tls.clone())),
+        )
+        .await
+        .with_context(|| format!("Failed to connect to socket mode: {host}"))?;
+
+        tokio::time::timeout(HELLO_TIMEOUT, wait_for_hello(&mut socket))
+            .await
+            .with_context(|| "Slack did not say hello on socket mode in time")??;
+        debug!("Connected to socket mode: {host}");
+
+        Ok(socket)
+    }
+
+    // Slack closes the connection from time to time, and asks to reconnect
+    async fn reopen(&self) -> Socket {
+        loop {
+            match self.open().await {
+                Ok(socket) => {
+                    info!("Reconnected to socket mode");
+                    return socket;
+                }
+                Err(e) => {
+                    warn!("{:?}", e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    // Until the connection is closed
+    async fn serve<S, F, Fut>(&self, socket: &mut Socket, state: &Arc<S>, handler: &F)
+    where
+        F: Fn(SlackInteractionEvent, Arc<SlackHttpClient>, Arc<S>) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        while let Some(message) = socket.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Socket mode connection is lost: {e}");
+                    return;
+                }
+            };
+            let envelope: Envelope = match serde_json::from_str(text.as_str()) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    warn!("Failed to parse socket mode message: {e}");
+                    continue;
+                }
+            };
+            // NOTE: Slack sends the envelope again unless it is acknowledged within 3 seconds
+            if let Some(envelope_id) = &envelope.envelope_id {
+                let ack = serde_json::json!({ "envelope_id": envelope_id }).to_string();
+                if let Err(e) = socket.send(Message::text(ack)).await {
+                    warn!("Failed to acknowledge socket mode message: {e}");
+                    return;
+                }
+            }
+
+            match (envelope.kind.as_str(), envelope.payload) {
+                ("disconnect", _) => return,
+                ("interactive", Some(payload)) => {
+                    match serde_json::from_value::<SlackInteractionEvent>(payload) {
+                        Ok(event) => {
+                            let future = handler(event, self.client.clone(), state.clone());
+                            tokio::spawn(async move {
+                                if let Err(e) = future.await {
+                                    warn!("{:?}", e);
+                                }
+                            });
+                        }
+                        Err(e) => warn!("Failed to parse interaction: {e}"),
+                    }
+                }
+                (kind, _) => debug!("Socket mode message is ignored: {kind}"),
+            }
+        }
+    }
+}
+
+async fn wait_for_hello(socket: &mut Socket) -> Result<()> {
+    while let Some(message) = socket.next().await {
+        if let Message::Text(text) = message? {
+            let envelope: Envelope = serde_json::from_str(text.as_str())
+                .with_context(|| "Failed to parse socket mode message")?;
+            if envelope.kind == "hello" {
+                return Ok(());
+            }
+        }
+    }
+
+    bail!("Socket mode connection was closed before hello")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::super::client;
+    use super::super::content::build_actions_block;
+    use super::*;
+    use crate::services::fake_slack::{FakeSlack, FakeSlackOptions};
+
+    #[tokio::test]
+    async fn should_receive_click_from_fake_slack() {
+        let fake_slack = FakeSlack::start(&FakeSlackOptions {
+            host: "127.0.0.1".into(),
+            port: 0,
+            usergroups: Vec::new(),
+        })
+        .await
+        .unwrap();
+        let network = NetworkOptions {
+            slack_api_url: Some(fake_slack.api_url()),
+            ..Default::default()
+        };
+        let client = Arc::new(client::new_client(&network).unwrap());
+        let token = SlackApiToken::new("xoxb-bot-token".into());
+        let posted = client
+            .open_session(&token)
+            .chat_post_message(&SlackApiChatPostMessageRequest::new(
+                "C1234567890".into(),
+                SlackMessageContent::new().with_blocks(vec![build_actions_block()]),
+            ))
+            .await
+            .unwrap();
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let socket_mode = SocketMode::connect(
+            client,
+            &"xapp-app-token".into(),
+            &network,
+            Arc::new(sender),
+            |event, _, sender: Arc<mpsc::UnboundedSender<SlackInteractionEvent>>| async move {
+                sender.send(event)?;
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+        let res = reqwest::Client::new()
+            .post(fake_slack.api_url().replace("/api", "/_fake/click"))
+            .body(r#"{"button": "approve", "user": "U1234567890"}"#)
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let SlackInteractionEvent::BlockActions(event) = receiver.recv().await.unwrap() else {
+            panic!("Not block actions");
+        };
+        assert_eq!(event.user.unwrap().id.0, "U1234567890");
+        assert_eq!(event.message.unwrap().origin.ts, posted.ts);
+        assert_eq!(
+            event.actions.unwrap()[0].action_id.0,
+            super::super::SLACK_APPROVAL_APPROVE_ACTION_ID
+        );
+
+        socket_mode.shutdown();
+        fake_slack.shutdown();
+    }
+}
@@ -0,0 +1,121 @@
+use slack_morphism::prelude::*;
+use tracing::{info, warn};
+
+use super::approval_metadata::{ApprovalMetadata, ApprovalStatus};
+use super::{fetch_history, replace_actions_block, update_message};
+use crate::services::ci::CiInfo;
+
+// Marks the older pending approvals of the same key as superseded and removes their buttons
+// Failures are only logged since the new approval can still be processed
+pub async fn supersede_pending_approvals<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    current: &ApprovalMetadata,
+    ci_info: &dyn CiInfo,
+    ts: &SlackTs,
+) where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let messages = match fetch_history(session, channel_id, Some(ts)).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!("Skipped superseding pending approvals: {:?}", e);
+            return;
+        }
+    };
+
+    for message in messages {
+        let Some(metadata) = message
+            .content
+            .metadata
+            .as_ref()
+            .and_then(ApprovalMetadata::from_slack_metadata)
+        else {
+            continue;
+        };
+        if !is_superseded_by(&metadata, current) {
+            continue;
+        }
+
+        info!(
+            "Superseding pending approval of run #{}, ts: {}",
+            metadata.run_number, message.origin.ts
+        );
+        let blocks = superseded_blocks(
+            message.content.blocks.as_deref().unwrap_or_default(),
+            ci_info,
+        );
+        let content = SlackMessageContent::new()
+            .with_blocks(blocks)
+            .with_metadata(
+                metadata
+                    .with_status(ApprovalStatus::Superseded)
+                    .to_slack_metadata(),
+            );
+        if let Err(e) = update_message(session, channel_id, content, &message.origin.ts).await {
+            warn!("Failed to supersede pending approval: {:?}", e);
+        }
+    }
+}
+
+fn is_superseded_by(candidate: &ApprovalMetadata, current: &ApprovalMetadata) -> bool {
+    candidate.status == ApprovalStatus::Pending
+        && candidate.key == current.key
+        && candidate.run_id != current.run_id
+}
+
+fn superseded_blocks(blocks: &[SlackBlock], ci_info: &dyn CiInfo) -> Vec<SlackBlock> {
+    let run = format!("run #{}", ci_info.run_number());
+    let text = match ci_info.run_url() {
+        Some(url) => format!("⏭️Superseded by <{url}|{run}>"),
+        None => format!("⏭️Superseded by {run}"),
+    };
+    replace_actions_block(blocks, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+    use rstest::rstest;
+
+    fn metadata(key: &str, run_id: &str, status: ApprovalStatus) -> ApprovalMetadata {
+        ApprovalMetadata {
+            key: key.into(),
+            run_id: run_id.into(),
+            run_number: run_id.into(),
+            status,
+        }
+    }
+
+    #[rstest]
+    #[case(metadata("repo/wf", "1", ApprovalStatus::Pending), true)]
+    #[case(metadata("repo/wf", "1", ApprovalStatus::Approved), false)]
+    #[case(metadata("repo/wf", "1", ApprovalStatus::Rejected), false)]
+    #[case(metadata("repo/wf", "1", ApprovalStatus::Superseded), false)]
+    #[case(metadata("repo/other", "1", ApprovalStatus::Pending), false)]
+    #[case(metadata("repo/wf", "2", ApprovalStatus::Pending), false)]
+    fn test_is_superseded_by(#[case] candidate: ApprovalMetadata, #[case] expected: bool) {
+        let current = metadata("repo/wf", "2", ApprovalStatus::Pending);
+        assert_eq!(is_superseded_by(&candidate, &current), expected);
+    }
+
+    #[test]
+    fn should_replace_buttons_with_superseded_section() {
+        let github_info = GitHubInfo::for_test();
+        let blocks: Vec<SlackBlock> = slack_blocks![
+            some_into(SlackSectionBlock::new().with_text(md!("header"))),
+            some_into(SlackActionsBlock::new(vec![]))
+        ];
+
+        let actual = superseded_blocks(&blocks, &github_info);
+
+        let expected: Vec<SlackBlock> = slack_blocks![
+            some_into(SlackSectionBlock::new().with_text(md!("header"))),
+            some_into(SlackSectionBlock::new().with_text(md!(
+                "⏭️Superseded by <https://github.com/octocat/Hello-World/actions/runs/42|run #7>"
+            )))
+        ];
+        assert_eq!(actual, expected);
+    }
+}
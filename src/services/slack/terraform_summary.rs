@@ -0,0 +1,153 @@
+use slack_morphism::prelude::*;
+
+use super::SLACK_APPROVAL_APPROVE_ACTION_ID;
+use super::content::escape_mrkdwn;
+use crate::services::terraform::TerraformPlan;
+
+// Number of destroyed or replaced resources listed in each section
+const MAX_LISTED_RESOURCES: usize = 20;
+
+// Counts of the plan followed by the resources to be destroyed so that no one misses them
+pub fn build_plan_blocks(plan: &TerraformPlan) -> Vec<SlackBlock> {
+    let icon = if plan.has_destroys() {
+        "⚠️"
+    } else {
+        "🏗️"
+    };
+    let mut blocks: Vec<SlackBlock> = vec![
+        SlackSectionBlock::new()
+            .with_text(md!(format!(
+                "{icon}*Terraform plan:* `{}` to add, `{}` to change, `{}` to destroy",
+                plan.add, plan.change, plan.destroy
+            )))
+            .into(),
+    ];
+
+    for (name, addresses) in [
+        ("🔥*Destroyed:*", &plan.destroyed),
+        ("♻️*Replaced:*", &plan.replaced),
+    ] {
+        if !addresses.is_empty() {
+            blocks.push(
+                SlackSectionBlock::new()
+                    .with_text(md!(format!("{name}\n{}", list_resources(addresses))))
+                    .into(),
+            );
+        }
+    }
+
+    blocks
+}
+
+fn list_resources(addresses: &[String]) -> String {
+    let mut lines: Vec<String> = addresses
+        .iter()
+        .take(MAX_LISTED_RESOURCES)
+        .map(|address| format!("• `{}`", escape_mrkdwn(address)))
+        .collect();
+    if addresses.len() > MAX_LISTED_RESOURCES {
+        lines.push(format!(
+            "…and {} more",
+            addresses.len() - MAX_LISTED_RESOURCES
+        ));
+    }
+
+    lines.join("\n")
+}
+
+// Asks to confirm in a dialog before the approval is sent
+pub fn require_destroy_confirmation(blocks: &mut [SlackBlock], plan: &TerraformPlan) {
+    let confirm = SlackBlockConfirmItem::new(
+        pt!("Destroy resources?"),
+        md!(format!(
+            "This plan destroys {} resource(s), including {} replaced. Do you really want to approve?",
+            plan.destroy,
+            plan.replaced.len()
+        )),
+        pt!("Approve"),
+        pt!("Cancel"),
+    )
+    .with_style("danger".into());
+
+    for block in blocks {
+        let SlackBlock::Actions(actions) = block else {
+            continue;
+        };
+        for element in &mut actions.elements {
+            if let SlackActionBlockElement::Button(button) = element
+                && button.action_id.0 == SLACK_APPROVAL_APPROVE_ACTION_ID
+            {
+                button.confirm = Some(confirm.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::slack::content::build_actions_block;
+
+    fn plan() -> TerraformPlan {
+        TerraformPlan {
+            add: 2,
+            change: 1,
+            destroy: 2,
+            destroyed: vec!["aws_db_instance.main".into()],
+            replaced: vec!["aws_instance.web[0]".into()],
+        }
+    }
+
+    #[test]
+    fn should_build_plan_blocks() {
+        let actual = build_plan_blocks(&plan());
+
+        let expected: Vec<SlackBlock> = vec![
+            SlackSectionBlock::new()
+                .with_text(md!(
+                    "⚠️*Terraform plan:* `2` to add, `1` to change, `2` to destroy"
+                ))
+                .into(),
+            SlackSectionBlock::new()
+                .with_text(md!("🔥*Destroyed:*\n• `aws_db_instance.main`"))
+                .into(),
+            SlackSectionBlock::new()
+                .with_text(md!("♻️*Replaced:*\n• `aws_instance.web[0]`"))
+                .into(),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_list_up_to_max_resources() {
+        let addresses: Vec<String> = (0..MAX_LISTED_RESOURCES + 2)
+            .map(|i| format!("aws_instance.web[{i}]"))
+            .collect();
+
+        let actual = list_resources(&addresses);
+
+        assert_eq!(actual.lines().count(), MAX_LISTED_RESOURCES + 1);
+        assert!(actual.ends_with("…and 2 more"));
+    }
+
+    #[test]
+    fn should_require_confirmation_to_approve() {
+        let mut blocks = vec![build_actions_block()];
+
+        require_destroy_confirmation(&mut blocks, &plan());
+
+        let SlackBlock::Actions(actions) = &blocks[0] else {
+            panic!("Actions block should be kept");
+        };
+        let confirms: Vec<bool> = actions
+            .elements
+            .iter()
+            .map(|element| match element {
+                SlackActionBlockElement::Button(button) => button.confirm.is_some(),
+                _ => false,
+            })
+            .collect();
+        // Only the approve button asks to confirm
+        assert_eq!(confirms, vec![true, false]);
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Result, bail};
+use slack_morphism::prelude::*;
+use tracing::info;
+
+use super::fetch_history;
+use crate::services::github::github_inputs::GitHubInputs;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SlackThread {
+    pub ts: SlackTs,
+    // Also post the reply to the channel
+    pub reply_broadcast: bool,
+}
+
+// Returns the thread to post the approval into, or None to post it to the channel
+pub async fn resolve_thread<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    github_inputs: &GitHubInputs,
+) -> Result<Option<SlackThread>>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let ts = match (&github_inputs.thread_ts, &github_inputs.thread_match) {
+        (Some(ts), _) => {
+            // NOTE: A ts identifies a message only within a channel
+            if github_inputs.channel_ids.len() > 1 {
+                bail!(
+                    "Input 'thread-ts' cannot be used with multiple channels. Use 'thread-match' instead"
+                );
+            }
+            ts.clone().into()
+        }
+        (None, Some(text)) => {
+            let messages = fetch_history(session, channel_id, None).await?;
+            match find_message_ts(&messages, text) {
+                Some(ts) => ts,
+                None => bail!(
+                    "No message matching the text was found in the channel. text: {text}, channel_id: {channel_id}"
+                ),
+            }
+        }
+        (None, None) => return Ok(None),
+    };
+    info!(
+        "Posting approval into thread: {}, channel_id: {}",
+        ts, channel_id
+    );
+
+    Ok(Some(SlackThread {
+        ts,
+        reply_broadcast: github_inputs.reply_broadcast,
+    }))
+}
+
+// History is ordered from the newest, so the latest matching message is picked
+fn find_message_ts(messages: &[SlackHistoryMessage], text: &str) -> Option<SlackTs> {
+    messages
+        .iter()
+        .find(|message| {
+            message
+                .content
+                .text
+                .as_ref()
+                .is_some_and(|message_text| message_text.contains(text))
+        })
+        .map(|message| {
+            // Reply to the parent when the matched message is a reply in a thread
+            message
+                .origin
+                .thread_ts
+                .clone()
+                .unwrap_or_else(|| message.origin.ts.clone())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn message(ts: &str, thread_ts: Option<&str>, text: Option<&str>) -> SlackHistoryMessage {
+        SlackHistoryMessage::new(
+            SlackMessageOrigin::new(ts.into()).opt_thread_ts(thread_ts.map(|ts| ts.into())),
+            SlackMessageContent::new().opt_text(text.map(|text| text.into())),
+            SlackMessageSender::new(),
+            SlackParentMessageParams::new(),
+        )
+    }
+
+    #[rstest]
+    #[case("Release v1.2.0", Some("3.0"))]
+    #[case("Release v1.1.0", Some("1.0"))]
+    #[case("Release", Some("3.0"))]
+    #[case("Release v1.3.0", None)]
+    fn test_find_message_ts(#[case] text: &str, #[case] expected: Option<&str>) {
+        let messages = vec![
+            message("4.0", None, None),
+            message("3.0", None, Some("Release v1.2.0 started")),
+            message("2.0", Some("1.0"), Some("Release v1.1.0 is ready")),
+            message("1.0", None, Some("Release")),
+        ];
+
+        let actual = find_message_ts(&messages, text);
+        assert_eq!(actual, expected.map(|ts| ts.into()));
+    }
+}
@@ -0,0 +1,308 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use slack_morphism::prelude::*;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::approval_metadata::{ApprovalMetadata, ApprovalStatus};
+use super::client::SlackHttpClient;
+use super::socket_mode::SocketMode;
+use super::thread::{self, SlackThread};
+use super::{
+    PostedMessage, SLACK_APPROVAL_APPROVE_ACTION_ID, SLACK_APPROVAL_REJECT_ACTION_ID,
+    fetch_user_ids_from_groups, post_message, post_or_join, replace_actions_block, supersede,
+    update_messages,
+};
+use crate::services::chat::{Decision, Interaction, Outcome, Transport};
+use crate::services::ci::CiInfo;
+use crate::services::github::github_inputs::{GitHubInputs, Mode};
+use crate::services::network::NetworkOptions;
+
+type Interactions = mpsc::UnboundedReceiver<SlackInteractionEvent>;
+
+// Posts the approval to Slack and receives the clicks over Socket Mode
+pub(super) struct SlackTransport<'a> {
+    client: Arc<SlackHttpClient>,
+    token: SlackApiToken,
+    github_inputs: &'a GitHubInputs,
+    ci_info: &'a dyn CiInfo,
+    content: SlackMessageContent,
+    metadata: ApprovalMetadata,
+    // Every copy is updated once someone decides
+    pub(super) messages: Vec<PostedMessage>,
+    socket_mode: Option<(SocketMode, Interactions)>,
+}
+
+impl<'a> SlackTransport<'a> {
+    pub(super) fn new(
+        client: Arc<SlackHttpClient>,
+        token: SlackApiToken,
+        github_inputs: &'a GitHubInputs,
+        ci_info: &'a dyn CiInfo,
+        content: SlackMessageContent,
+        metadata: ApprovalMetadata,
+    ) -> Self {
+        Self {
+            client,
+            token,
+            github_inputs,
+            ci_info,
+            content,
+            metadata,
+            messages: vec![],
+            socket_mode: None,
+        }
+    }
+
+    // Clicks are received from here on
+    pub(super) async fn listen(
+        &mut self,
+        app_token: &SlackApiTokenValue,
+        network: &NetworkOptions,
+    ) -> Result<()> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let socket_mode = SocketMode::connect(
+            self.client.clone(),
+            app_token,
+            network,
+            Arc::new(sender),
+            forward_interaction,
+        )
+        .await?;
+        self.socket_mode = Some((socket_mode, receiver));
+
+        Ok(())
+    }
+
+    pub(super) fn shutdown(self) {
+        if let Some((socket_mode, _)) = self.socket_mode {
+            socket_mode.shutdown();
+        }
+    }
+}
+
+impl Transport for SlackTransport<'_> {
+    async fn post(&mut self, channel_id: &str) -> Result<()> {
+        let channel_id = SlackChannelId::new(channel_id.into());
+        let session = self.client.open_session(&self.token);
+        let thread = thread::resolve_thread(&session, &channel_id, self.github_inputs)
+            .await
+            .with_context(|| "Failed to resolve thread to post approval into")?;
+        let ts = post_or_join(
+            &session,
+            &channel_id,
+            self.content.clone(),
+            thread.as_ref(),
+            self.github_inputs.join_channel,
+        )
+        .await?;
+
+        if self.github_inputs.supersede_pending && self.github_inputs.mode == Mode::Approval {
+            supersede::supersede_pending_approvals(
+                &session,
+                &channel_id,
+                &self.metadata,
+                self.ci_info,
+                &ts,
+            )
+            .await;
+        }
+
+        self.messages.push(PostedMessage {
+            channel_id,
+            ts,
+            // NOTE: Notices should not be broadcasted to the channel
+            thread: thread.map(|thread| SlackThread {
+                reply_broadcast: false,
+                ..thread
+            }),
+        });
+
+        Ok(())
+    }
+
+    async fn update(&self, outcome: &Outcome) -> Result<()> {
+        let status = match outcome {
+            Outcome::Approved { .. } => ApprovalStatus::Approved,
+            Outcome::Rejected { .. } => ApprovalStatus::Rejected,
+            Outcome::Cancelled => ApprovalStatus::Cancelled,
+            Outcome::TimedOut => ApprovalStatus::TimedOut,
+        };
+        let blocks = self.content.blocks.clone().unwrap_or_default();
+        let content = SlackMessageContent::new()
+            .with_blocks(replace_actions_block(&blocks, &outcome.text()))
+            .with_metadata(self.metadata.with_status(status).to_slack_metadata());
+
+        update_messages(
+            &self.client.open_session(&self.token),
+            &self.messages,
+            content,
+        )
+        .await
+    }
+
+    // Posts next to the copy of the approval in the channel where the button was clicked
+    async fn notice(&self, interaction: &Interaction, text: &str) -> Result<()> {
+        let channel_id = SlackChannelId::new(interaction.channel_id.clone());
+        let thread = self
+            .messages
+            .iter()
+            .find(|message| message.channel_id == channel_id)
+            .and_then(|message| message.thread.as_ref());
+        let content = SlackMessageContent::new().with_text(text.into());
+        post_message(
+            &self.client.open_session(&self.token),
+            &channel_id,
+            content,
+            thread,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+        let user_ids = fetch_user_ids_from_groups(
+            &self.client.open_session(&self.token),
+            &vec![SlackUserGroupId::new(group_id.into())],
+        )
+        .await?;
+
+        Ok(user_ids.into_iter().map(|user_id| user_id.0).collect())
+    }
+
+    async fn next_interaction(&mut self) -> Result<Interaction> {
+        let Some((_, interactions)) = &mut self.socket_mode else {
+            bail!("Not connected to socket mode");
+        };
+        while let Some(event) = interactions.recv().await {
+            match to_interaction(event, &self.messages) {
+                Some(interaction) => return Ok(interaction),
+                None => continue,
+            }
+        }
+
+        bail!("Socket mode connection was closed")
+    }
+}
+
+async fn forward_interaction(
+    event: SlackInteractionEvent,
+    _client: Arc<SlackHttpClient>,
+    sender: Arc<mpsc::UnboundedSender<SlackInteractionEvent>>,
+) -> Result<()> {
+    sender
+        .send(event)
+        .with_context(|| "Approval is not waiting for clicks anymore")
+}
+
+// None for events other than the clicks on the buttons of the copies this run posted
+fn to_interaction(event: SlackInteractionEvent, messages: &[PostedMessage]) -> Option<Interaction> {
+    let SlackInteractionEvent::BlockActions(block_actions) = event else {
+        debug!("Interaction is ignored: {:?}", event);
+        return None;
+    };
+    let user_id = block_actions.user?.id;
+    let channel_id = block_actions.channel?.id;
+    let SlackInteractionActionContainer::Message(container) = block_actions.container else {
+        debug!("Interaction is ignored as it is not on a message");
+        return None;
+    };
+    // NOTE: Slack sends each click to only one connection of the app, so runs sharing the app may get the clicks of each other
+    if !messages
+        .iter()
+        .any(|message| message.channel_id == channel_id && message.ts == container.message_ts)
+    {
+        warn!(
+            "Click on a message of another approval is ignored. channel_id: {channel_id}, ts: {}",
+            container.message_ts
+        );
+        return None;
+    }
+    let action = block_actions.actions?.into_iter().next()?;
+    let decision = match action.action_id.0.as_str() {
+        SLACK_APPROVAL_APPROVE_ACTION_ID => Decision::Approved,
+        SLACK_APPROVAL_REJECT_ACTION_ID => Decision::Rejected,
+        action_id => {
+            debug!("Action is ignored: {action_id}");
+            return None;
+        }
+    };
+
+    Some(Interaction {
+        mention: user_id.to_slack_format(),
+        user_id: user_id.0,
+        channel_id: channel_id.0,
+        decision,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn messages() -> Vec<PostedMessage> {
+        vec![PostedMessage {
+            channel_id: "C1234567890".into(),
+            ts: "1700000000.000100".into(),
+            thread: None,
+        }]
+    }
+
+    fn block_actions(action_id: &str, message_ts: &str) -> SlackInteractionEvent {
+        serde_json::from_value(json!({
+            "type": "block_actions",
+            "team": { "id": "T1234567890" },
+            "user": { "id": "U1234567890" },
+            "api_app_id": "A1234567890",
+            "container": { "type": "message", "message_ts": message_ts, "channel_id": "C1234567890" },
+            "trigger_id": "trigger",
+            "channel": { "id": "C1234567890" },
+            "actions": [{ "type": "button", "action_id": action_id }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_convert_click_to_interaction() {
+        let actual = to_interaction(
+            block_actions(SLACK_APPROVAL_REJECT_ACTION_ID, "1700000000.000100"),
+            &messages(),
+        );
+
+        assert_eq!(
+            actual,
+            Some(Interaction {
+                user_id: "U1234567890".into(),
+                mention: "<@U1234567890>".into(),
+                channel_id: "C1234567890".into(),
+                decision: Decision::Rejected,
+            })
+        );
+    }
+
+    #[test]
+    fn should_ignore_other_actions() {
+        assert_eq!(
+            to_interaction(
+                block_actions("other-app-button", "1700000000.000100"),
+                &messages()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn should_ignore_clicks_on_other_approvals() {
+        assert_eq!(
+            to_interaction(
+                block_actions(SLACK_APPROVAL_APPROVE_ACTION_ID, "1700000099.000100"),
+                &messages()
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{Context, Result, bail};
+use slack_morphism::prelude::*;
+use tracing::info;
+
+use super::block_limits::MAX_BLOCKS;
+use super::content::escape_mrkdwn;
+use super::thread::SlackThread;
+use super::{client, post_message, retry, update_message};
+use crate::services::ci::CiInfo;
+use crate::services::github::update_inputs::{AppendTo, UpdateInputs};
+use crate::services::template::TemplateContext;
+
+// Appends status like "Deploy succeeded" to an approval posted by an earlier step
+pub async fn handle_slack_update(ci_info: &dyn CiInfo, update_inputs: &UpdateInputs) -> Result<()> {
+    let client = client::new_client(&update_inputs.network)?;
+    let token = SlackApiToken::new(update_inputs.bot_token.clone());
+    let session = client.open_session(&token);
+
+    let status = TemplateContext::new(ci_info)?
+        .with_escape(escape_mrkdwn)
+        .render(&update_inputs.status)
+        .with_context(|| "Failed to render status")?;
+    let channel_id = &update_inputs.channel_id;
+
+    match update_inputs.append_to {
+        AppendTo::Thread => {
+            let thread = SlackThread {
+                ts: update_inputs
+                    .thread_ts
+                    .clone()
+                    .unwrap_or_else(|| update_inputs.message_ts.clone()),
+                reply_broadcast: false,
+            };
+            let content = SlackMessageContent::new().with_text(status.clone());
+            post_message(&session, channel_id, content, Some(&thread)).await?;
+        }
+        AppendTo::Message => {
+            append_status_to_message(&session, channel_id, &update_inputs.message_ts, &status)
+                .await?;
+        }
+    }
+
+    info!("Status appended: {}", status);
+
+    Ok(())
+}
+
+pub(super) async fn append_status_to_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    ts: &SlackTs,
+    status: &str,
+) -> Result<()>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let message = fetch_message(session, channel_id, ts).await?;
+    let content = append_status(message.content, status)?;
+    update_message(session, channel_id, content, ts).await
+}
+
+async fn fetch_message<SDHC>(
+    session: &SlackClientSession<'_, SDHC>,
+    channel_id: &SlackChannelId,
+    ts: &SlackTs,
+) -> Result<SlackHistoryMessage>
+where
+    SDHC: SlackClientHttpConnector + Send,
+{
+    let req = SlackApiConversationsHistoryRequest::new()
+        .with_channel(channel_id.clone())
+        .with_latest(ts.clone())
+        .with_inclusive(true)
+        .with_limit(1)
+        .with_include_all_metadata(true);
+    let res = retry::with_retry("conversations.history", || session.conversations_history(&req))
+        .await
+        .with_context(|| {
+            format!("Failed to fetch message. Have you added `channels:history` or `groups:history` scope? channel_id: {channel_id}")
+        })?;
+
+    // NOTE: Replies in a thread are not in the channel history unless they are broadcasted
+    match res.messages.into_iter().next() {
+        Some(message) if &message.origin.ts == ts => Ok(message),
+        _ => bail!(
+            "Message is not found in the channel. Use `append-to: thread` for a threaded approval. channel_id: {channel_id}, ts: {ts}"
+        ),
+    }
+}
+
+// Keeps the blocks and metadata of the approval and adds the status at the bottom
+fn append_status(content: SlackMessageContent, status: &str) -> Result<SlackMessageContent> {
+    let mut blocks = content.blocks.unwrap_or_default();
+    if blocks.len() >= MAX_BLOCKS {
+        bail!("Message has no room for the status. Use `append-to: thread` instead");
+    }
+    blocks.push(
+        SlackContextBlock::new(vec![SlackContextBlockElement::MarkDown(
+            SlackBlockMarkDownText::new(status.into()),
+        )])
+        .into(),
+    );
+
+    Ok(SlackMessageContent::new()
+        .opt_text(content.text)
+        .with_blocks(blocks)
+        .opt_metadata(content.metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_append_status() {
+        let metadata = SlackMessageMetadata::new("slack_approval".into());
+        let content = SlackMessageContent::new()
+            .with_text("Deploy".into())
+            .with_blocks(vec![SlackDividerBlock::new().into()])
+            .with_metadata(metadata.clone());
+
+        let actual = append_status(content, "🚀Deploy succeeded in 4m").unwrap();
+
+        assert_eq!(actual.text, Some("Deploy".into()));
+        assert_eq!(actual.metadata, Some(metadata));
+        assert_eq!(
+            actual.blocks,
+            Some(vec![
+                SlackDividerBlock::new().into(),
+                SlackContextBlock::new(vec![SlackContextBlockElement::MarkDown(
+                    SlackBlockMarkDownText::new("🚀Deploy succeeded in 4m".into()),
+                )])
+                .into(),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_fail_to_append_status_to_full_message() {
+        let content = SlackMessageContent::new()
+            .with_blocks(vec![SlackDividerBlock::new().into(); MAX_BLOCKS]);
+
+        let actual = append_status(content, "Deploy started").map_err(|e| e.to_string());
+
+        assert_eq!(
+            actual.map(|_| ()),
+            Err("Message has no room for the status. Use `append-to: thread` instead".into())
+        );
+    }
+}
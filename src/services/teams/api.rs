@@ -0,0 +1,294 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::services::github::github_inputs::TeamsInputs;
+
+// Scope of the token to call the Bot Connector service
+const BOT_SCOPE: &str = "https://api.botframework.com/.default";
+
+// Tokens are renewed before they expire while being sent
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(300);
+
+// Microsoft services other than the Bot Connector, which tests replace with a stand-in
+#[derive(Debug, Clone)]
+pub(super) struct Endpoints {
+    pub login_url: String,
+    pub graph_url: String,
+    // OpenID configuration of the tokens which Bot Framework sends
+    pub openid_url: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            login_url: "https://login.microsoftonline.com".into(),
+            graph_url: "https://graph.microsoft.com".into(),
+            openid_url: "https://login.botframework.com/v1/.well-known/openidconfiguration".into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+struct AccessToken {
+    scope: String,
+    value: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct ResourceResponse {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Members {
+    value: Vec<Member>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    id: String,
+}
+
+// Bot Connector REST API, and Microsoft Graph for the members of groups, with the credentials of the bot
+// https://learn.microsoft.com/en-us/azure/bot-service/rest-api/bot-framework-rest-connector-api-reference
+pub(super) struct TeamsApi {
+    client: reqwest::Client,
+    endpoints: Endpoints,
+    service_url: String,
+    app_id: String,
+    tenant_id: String,
+    app_password: String,
+    tokens: Mutex<Vec<AccessToken>>,
+}
+
+impl TeamsApi {
+    pub(super) fn new(
+        client: reqwest::Client,
+        endpoints: Endpoints,
+        teams: &TeamsInputs,
+        app_password: &str,
+    ) -> Self {
+        Self {
+            client,
+            endpoints,
+            service_url: teams.service_url.clone(),
+            app_id: teams.app_id.clone(),
+            tenant_id: teams.tenant_id.clone(),
+            app_password: app_password.into(),
+            tokens: Mutex::new(vec![]),
+        }
+    }
+
+    // Returns the ID of the activity
+    pub(super) async fn send_activity(
+        &self,
+        conversation_id: &str,
+        activity: &Value,
+    ) -> Result<String> {
+        let url = self.conversation_url(conversation_id, &["activities"])?;
+        let req = self.client.post(url).json(activity);
+        let res: ResourceResponse = self.send(req, BOT_SCOPE, "send activity").await?;
+
+        Ok(res.id)
+    }
+
+    pub(super) async fn update_activity(
+        &self,
+        conversation_id: &str,
+        activity_id: &str,
+        activity: &Value,
+    ) -> Result<()> {
+        let url = self.conversation_url(conversation_id, &["activities", activity_id])?;
+        let req = self.client.put(url).json(activity);
+        let _: Value = self.send(req, BOT_SCOPE, "update activity").await?;
+
+        Ok(())
+    }
+
+    pub(super) async fn reply_to_activity(
+        &self,
+        conversation_id: &str,
+        activity_id: &str,
+        activity: &Value,
+    ) -> Result<()> {
+        let url = self.conversation_url(conversation_id, &["activities", activity_id])?;
+        let req = self.client.post(url).json(activity);
+        let _: Value = self.send(req, BOT_SCOPE, "reply to activity").await?;
+
+        Ok(())
+    }
+
+    // Users in the group and its nested groups
+    pub(super) async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+        let scope = format!("{}/.default", self.endpoints.graph_url);
+        let mut url = format!(
+            "{}/v1.0/groups/{group_id}/transitiveMembers/microsoft.graph.user?$select=id",
+            self.endpoints.graph_url
+        );
+        let mut user_ids = vec![];
+        loop {
+            let res: Members = self
+                .send(self.client.get(&url), &scope, "list group members")
+                .await
+                .with_context(|| format!("group_id: {group_id}"))?;
+            user_ids.extend(res.value.into_iter().map(|member| member.id));
+            match res.next_link {
+                Some(next_link) => url = next_link,
+                None => break,
+            }
+        }
+
+        Ok(user_ids)
+    }
+
+    // NOTE: IDs of channel conversations have `:`, `@` and `;`, which are valid in a path segment and sent as they are
+    fn conversation_url(&self, conversation_id: &str, rest: &[&str]) -> Result<Url> {
+        let mut url = Url::parse(&self.service_url)
+            .with_context(|| format!("Invalid service URL: {}", self.service_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid service URL: {}", self.service_url))?
+            .pop_if_empty()
+            .extend(["v3", "conversations", conversation_id])
+            .extend(rest);
+
+        Ok(url)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        req: reqwest::RequestBuilder,
+        scope: &str,
+        what: &str,
+    ) -> Result<T> {
+        let token = self.token(scope).await?;
+        let res = req
+            .bearer_auth(token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to {what}"))?;
+        let status = res.status();
+        if !status.is_success() {
+            let body: Value = res.json().await.unwrap_or_default();
+            bail!("Failed to {what}: {}", error_message(&body, status));
+        }
+
+        // NOTE: Some responses have no body
+        let body = res.bytes().await?;
+        let body = if body.is_empty() {
+            b"{}".as_slice()
+        } else {
+            &body
+        };
+        serde_json::from_slice(body).with_context(|| format!("Failed to parse response to {what}"))
+    }
+
+    // Client credentials of the bot
+    async fn token(&self, scope: &str) -> Result<String> {
+        let mut tokens = self.tokens.lock().await;
+        let now = Instant::now();
+        tokens.retain(|token| token.expires_at > now + TOKEN_EXPIRY_MARGIN);
+        if let Some(token) = tokens.iter().find(|token| token.scope == scope) {
+            return Ok(token.value.clone());
+        }
+
+        let res = self
+            .client
+            .post(format!(
+                "{}/{}/oauth2/v2.0/token",
+                self.endpoints.login_url, self.tenant_id
+            ))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", &self.app_id),
+                ("client_secret", &self.app_password),
+                ("scope", scope),
+            ])
+            .send()
+            .await
+            .with_context(|| "Failed to get token of the bot")?;
+        let status = res.status();
+        if !status.is_success() {
+            let body: Value = res.json().await.unwrap_or_default();
+            bail!(
+                "Failed to get token of the bot: {}",
+                error_message(&body, status)
+            );
+        }
+        let res: TokenResponse = res.json().await?;
+        tokens.push(AccessToken {
+            scope: scope.into(),
+            value: res.access_token.clone(),
+            expires_at: now + Duration::from_secs(res.expires_in),
+        });
+
+        Ok(res.access_token)
+    }
+}
+
+// Bot Connector and Graph have `error.message`, and the login has `error_description`
+fn error_message(body: &Value, status: reqwest::StatusCode) -> String {
+    body["error"]["message"]
+        .as_str()
+        .or_else(|| body["error_description"].as_str())
+        .map_or_else(|| status.to_string(), String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    #[rstest]
+    #[case::bot_connector(json!({ "error": { "code": "BotNotInConversationRoster", "message": "The bot is not part of the conversation roster." } }), "The bot is not part of the conversation roster.")]
+    #[case::login(json!({ "error": "invalid_client", "error_description": "AADSTS7000215: Invalid client secret provided." }), "AADSTS7000215: Invalid client secret provided.")]
+    #[case::empty(Value::Null, "403 Forbidden")]
+    fn test_error_message(#[case] body: Value, #[case] expected: &str) {
+        assert_eq!(
+            error_message(&body, reqwest::StatusCode::FORBIDDEN),
+            expected
+        );
+    }
+
+    #[test]
+    fn should_build_conversation_url() {
+        let api = TeamsApi::new(
+            reqwest::Client::new(),
+            Endpoints::default(),
+            &TeamsInputs {
+                app_id: "app".into(),
+                tenant_id: "tenant".into(),
+                service_url: "https://smba.trafficmanager.net/teams".into(),
+                listen_address: None,
+            },
+            "password",
+        );
+
+        let actual = api
+            .conversation_url(
+                "19:4a1f2b3c@thread.tacv2;messageid=1700000000000",
+                &["activities"],
+            )
+            .unwrap();
+
+        assert_eq!(
+            actual.as_str(),
+            "https://smba.trafficmanager.net/teams/v3/conversations/19:4a1f2b3c@thread.tacv2;messageid=1700000000000/activities"
+        );
+    }
+}
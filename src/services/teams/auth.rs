@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use ring::signature::{RSA_PKCS1_2048_8192_SHA256, RsaPublicKeyComponents};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+// Issuer of the tokens which Bot Framework sends with the activities
+pub(super) const ISSUER: &str = "https://api.botframework.com";
+
+// Clocks of Bot Framework and the runner may differ
+const CLOCK_SKEW_SECS: u64 = 300;
+
+// Tokens with unknown keys make the keys fetched again at most this often, as anyone can send them
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+#[derive(Deserialize)]
+struct OpenIdConfiguration {
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize, Clone)]
+struct Jwk {
+    kid: String,
+    n: String,
+    e: String,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    aud: String,
+    exp: u64,
+    #[serde(default)]
+    nbf: Option<u64>,
+    // Matches the `serviceUrl` of the activity the token is sent with
+    #[serde(rename = "serviceUrl")]
+    service_url: String,
+}
+
+#[derive(Default)]
+struct Keys {
+    keys: Vec<Jwk>,
+    fetched_at: Option<Instant>,
+}
+
+// Tells the activities which Bot Framework sent from the requests by anyone else who can reach the runner
+// https://learn.microsoft.com/en-us/azure/bot-service/rest-api/bot-framework-rest-connector-authentication
+pub(super) struct Verifier {
+    client: reqwest::Client,
+    openid_url: String,
+    app_id: String,
+    // NOTE: Keys are fetched again when an unknown key is used, as they are rotated
+    keys: Mutex<Keys>,
+}
+
+impl Verifier {
+    pub(super) fn new(client: reqwest::Client, openid_url: &str, app_id: &str) -> Self {
+        Self {
+            client,
+            openid_url: openid_url.into(),
+            app_id: app_id.into(),
+            keys: Mutex::new(Keys::default()),
+        }
+    }
+
+    // `authorization` is the value of the `Authorization` header, and `service_url` is of the activity
+    pub(super) async fn verify(
+        &self,
+        authorization: Option<&str>,
+        service_url: &str,
+    ) -> Result<()> {
+        let Some(token) = authorization.and_then(|v| v.strip_prefix("Bearer ")) else {
+            bail!("No bearer token");
+        };
+        let mut parts = token.split('.');
+        let (Some(header), Some(claims), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Token is not a JWT");
+        };
+
+        let header: Header = decode_part(header).with_context(|| "Invalid JWT header")?;
+        if header.alg != "RS256" {
+            bail!("Unsupported JWT algorithm: {}", header.alg);
+        }
+        let key = self.find_key(&header.kid).await?;
+        let n = BASE64_URL_SAFE_NO_PAD.decode(&key.n)?;
+        let e = BASE64_URL_SAFE_NO_PAD.decode(&key.e)?;
+        let signature = BASE64_URL_SAFE_NO_PAD
+            .decode(signature)
+            .with_context(|| "Invalid JWT signature")?;
+        let message = &token[..header_and_claims_len(token)];
+        RsaPublicKeyComponents { n, e }
+            .verify(&RSA_PKCS1_2048_8192_SHA256, message.as_bytes(), &signature)
+            .map_err(|_| anyhow::anyhow!("JWT signature does not match"))?;
+
+        let claims: Claims = decode_part(claims).with_context(|| "Invalid JWT claims")?;
+        validate_claims(&claims, &self.app_id, service_url, now_secs())
+    }
+
+    async fn find_key(&self, kid: &str) -> Result<Jwk> {
+        let mut keys = self.keys.lock().await;
+        let is_fresh = keys
+            .fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < KEY_REFRESH_INTERVAL);
+        if !is_fresh && !keys.keys.iter().any(|key| key.kid == kid) {
+            *keys = Keys {
+                keys: self
+                    .fetch_keys()
+                    .await
+                    .with_context(|| "Failed to fetch signing keys of Bot Framework")?,
+                fetched_at: Some(Instant::now()),
+            };
+        }
+
+        keys.keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .cloned()
+            .with_context(|| format!("Unknown signing key: {kid}"))
+    }
+
+    async fn fetch_keys(&self) -> Result<Vec<Jwk>> {
+        let configuration: OpenIdConfiguration = self
+            .client
+            .get(&self.openid_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let jwks: Jwks = self
+            .client
+            .get(&configuration.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(jwks.keys)
+    }
+}
+
+fn validate_claims(claims: &Claims, app_id: &str, service_url: &str, now: u64) -> Result<()> {
+    if claims.iss != ISSUER {
+        bail!("Unexpected issuer: {}", claims.iss);
+    }
+    if claims.aud != app_id {
+        bail!("Token is not for this bot: {}", claims.aud);
+    }
+    if claims.service_url != service_url {
+        bail!(
+            "Token is for another service URL: {}, activity: {service_url}",
+            claims.service_url
+        );
+    }
+    if claims.exp + CLOCK_SKEW_SECS < now {
+        bail!("Token has expired");
+    }
+    if claims.nbf.is_some_and(|nbf| nbf > now + CLOCK_SKEW_SECS) {
+        bail!("Token is not valid yet");
+    }
+
+    Ok(())
+}
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T> {
+    let json = BASE64_URL_SAFE_NO_PAD.decode(part)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+// The signature is of `header.claims`
+fn header_and_claims_len(token: &str) -> usize {
+    token.rfind('.').unwrap_or(token.len())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use base64::prelude::BASE64_STANDARD;
+    use ring::rand::SystemRandom;
+    use ring::signature::{RSA_PKCS1_SHA256, RsaKeyPair};
+    use rstest::rstest;
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    // Throwaway key which only tests sign with
+    const TEST_KEY: &str = concat!(
+        "MIIEowIBAAKCAQEAtHwWt3ix1p43QiNAxg6zAAMrmPiYaOgGIykQh/Rgb3vDVXxg6MCk7kaNkJWP1U2J",
+        "18YgD4fGNQKscUhDiZPl2qpBjkwgGZkQ4fEaQkOsVETYRVkDsG/Jwkc7DzuCYgO6SXshQ2ZFo5HatLU+",
+        "C791GVuTj8ap1CiKHYwTkP7zJJqfABkTUfqPqPgeURnfYEa+8GSgUkXegjBfydz0YgWPWeH+2BZ/Vcaz",
+        "KEr2hFdVLtOywO+N63JziYMG/yRNYUQ5Wx8v91rI3mdtxdiA5O3nqRqEfsjpILHZkzZ6mwizAO6svoUG",
+        "p1eSAPKOJMtUTk+qnA3x61Qv+gWJRBULhbrGOQIDAQABAoIBAAJaH6HuMqoKE8zPDGb7jnT02eMQycOu",
+        "W3amxZ1hBfoJr/5RiaIJisuiAXqurq+BzT5E9GHfbhjT9NM2cL2LYSOGtFkz6sQ91UiOovErD++pd+J0",
+        "RepePtiHXxxlX841dQcIhQTrgWnB0j3kPF5tn9hO0fFGUqOXw2NqyXBbbfBpBhkGGL0Z2rDA+8U0Pqxj",
+        "AbT/pWnsqzSrJ1lsBhSzfnDAQ4JxRvYjlGVQ0inS2P7e1ay0Zs9EqjMEHujURZMHw/5D/bwT1vqWLg6K",
+        "LfhZJDwSu1WRK54FnS10wQlaQaji7gTTdt8KOd5LUH+oCha62muW+ifChZLgR9xKOWwIZ7UCgYEA3cgy",
+        "4S95WdK/vojWmjgkK2TjKrsdoc8o/4I/mOmJUCdwLEp3F6PPwB/nDho9ojy3wXA2BvShN9u/LHtA9Io4",
+        "YdJqOfFdDkhug/ozk4x1mb9i1lYN+/gaO34YZ2JW16pz/PLnwUsdrA2RwVFS+6CnHctm4JxaKjh26yuP",
+        "ngh7TSMCgYEA0FTBjv7lvxiuUMdmIw5+Or1bXkyd0SaKrYpnxcMHYoNiQfcyKGJ9l9zNfk/wh1PqgGkZ",
+        "1IQ7wuX18+2prO/nJXpIsalbwEsCVQcFtgPKCgv/hC9+NAo6nhn5TIUuXbkOxJIeaSiAXX3smiNrg4z7",
+        "35dNeygMBuVPXvV3RCrpGvMCgYEAvqfC7YiVRp2AznfuHIeUuGUzUGGo+JgRA43hxD9HFsvAh6LAPGdL",
+        "SjtisuHmCBkwUq93EoWsLkvPTZrrXQ2RqK5tOUB/oa6sqvIk8nu2ASTg/6bcwLYpPumIax/DzgSvT001",
+        "5BoB+Q11Nn4F22DhKSDZYM+oR1FhEYvSG6Xo8YcCgYANsPRDhIbuxx11m5Ufyq0LCTS+/sQEYccNXqIx",
+        "Qf7zP3UupqM6OjbMyysKGRDSo/YuWDnlWIdg6KIAVOcowV9sfBvY6StpeMR/eAWtD5SwkldJhaGCpAV0",
+        "LHrQx5Pjfuz0iDHNcWb2PfdVrew76gEHkbb62AfqUBmmQPWEmllVdwKBgCwwTxVbONdkRS1Dgkt6Qi5H",
+        "onH6orTjwOIzngwrqkQsABKZTdnGz9r1u8M3XnZ1FkXtTF2hyqkmbH9ng+Zvg/mY+DtlI+/31IB7NY/w",
+        "+1RMeQlpmPpwQsg/ibsQnsTgUcJo40LUzY/OwGYljrE5qH8ZSyLFi7u2T4ykzsYjA7do",
+    );
+
+    pub(in crate::services::teams) const TEST_KID: &str = "test-key";
+
+    pub(in crate::services::teams) fn test_key_pair() -> RsaKeyPair {
+        RsaKeyPair::from_der(&BASE64_STANDARD.decode(TEST_KEY).unwrap()).unwrap()
+    }
+
+    // JWKS which the stand-in of Bot Framework serves
+    pub(in crate::services::teams) fn test_jwks() -> Value {
+        let components: RsaPublicKeyComponents<Vec<u8>> = test_key_pair().public().into();
+        json!({
+            "keys": [{
+                "kty": "RSA",
+                "kid": TEST_KID,
+                "n": BASE64_URL_SAFE_NO_PAD.encode(components.n),
+                "e": BASE64_URL_SAFE_NO_PAD.encode(components.e),
+            }],
+        })
+    }
+
+    pub(in crate::services::teams) fn sign(claims: &Value) -> String {
+        let header = json!({ "alg": "RS256", "kid": TEST_KID, "typ": "JWT" });
+        let message = format!(
+            "{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+            BASE64_URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let key_pair = test_key_pair();
+        let mut signature = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                message.as_bytes(),
+                &mut signature,
+            )
+            .unwrap();
+
+        format!("{message}.{}", BASE64_URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    const SERVICE_URL: &str = "https://smba.trafficmanager.net/teams/";
+
+    #[rstest]
+    #[case::valid(ISSUER, "app", 1000, None, SERVICE_URL, true)]
+    #[case::other_issuer("https://example.com", "app", 1000, None, SERVICE_URL, false)]
+    #[case::other_audience(ISSUER, "other-app", 1000, None, SERVICE_URL, false)]
+    #[case::within_skew(ISSUER, "app", 1000 - CLOCK_SKEW_SECS, None, SERVICE_URL, true)]
+    #[case::expired(ISSUER, "app", 1000 - CLOCK_SKEW_SECS - 1, None, SERVICE_URL, false)]
+    #[case::not_yet(ISSUER, "app", 2000, Some(1000 + CLOCK_SKEW_SECS + 1), SERVICE_URL, false)]
+    #[case::other_service_url(ISSUER, "app", 1000, None, "https://example.com/", false)]
+    fn test_validate_claims(
+        #[case] iss: &str,
+        #[case] aud: &str,
+        #[case] exp: u64,
+        #[case] nbf: Option<u64>,
+        #[case] service_url: &str,
+        #[case] expected: bool,
+    ) {
+        let claims = Claims {
+            iss: iss.into(),
+            aud: aud.into(),
+            exp,
+            nbf,
+            service_url: service_url.into(),
+        };
+
+        assert_eq!(
+            validate_claims(&claims, "app", SERVICE_URL, 1000).is_ok(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result, bail};
+use http::{Method, Request, Response, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::auth::Verifier;
+use super::card::{APPROVE_VERB, REJECT_VERB};
+use crate::services::chat::callback::Route;
+use crate::services::chat::{Decision, Interaction};
+
+// Name of the invoke which Action.Execute sends
+const ACTION_INVOKE: &str = "adaptiveCard/action";
+
+// Activity which Bot Framework sends to the messaging endpoint
+// https://learn.microsoft.com/en-us/microsoftteams/platform/task-modules-and-cards/cards/universal-actions-for-adaptive-cards/work-with-universal-actions-for-adaptive-cards
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Activity {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: Option<String>,
+    // Checked against the token, which Bot Framework signs for the service URL
+    service_url: String,
+    from: Account,
+    conversation: Conversation,
+    #[serde(default)]
+    value: Option<InvokeValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Account {
+    #[serde(default)]
+    name: Option<String>,
+    // Microsoft Entra object ID, which `authorized-users` and groups have
+    #[serde(default)]
+    aad_object_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Conversation {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct InvokeValue {
+    action: CardAction,
+}
+
+#[derive(Deserialize)]
+struct CardAction {
+    verb: String,
+    #[serde(default)]
+    data: Value,
+}
+
+// Reads the clicks on the card of this approval, which Bot Framework signs
+pub(super) struct ActivityRoute {
+    pub(super) approval_id: String,
+    pub(super) verifier: Verifier,
+}
+
+impl Route for ActivityRoute {
+    async fn route(
+        &self,
+        req: Request<Incoming>,
+        sender: &mpsc::UnboundedSender<Interaction>,
+    ) -> Response<Full<Bytes>> {
+        route(req, &self.approval_id, &self.verifier, sender).await
+    }
+}
+
+async fn route(
+    req: Request<Incoming>,
+    approval_id: &str,
+    verifier: &Verifier,
+    sender: &mpsc::UnboundedSender<Interaction>,
+) -> Response<Full<Bytes>> {
+    if req.method() != Method::POST {
+        return to_response(StatusCode::NOT_FOUND, &Value::Null);
+    }
+    let authorization = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let activity = match read_activity(req).await {
+        Ok(activity) => activity,
+        Err(e) => {
+            warn!("{e:#}");
+            return to_response(StatusCode::BAD_REQUEST, &Value::Null);
+        }
+    };
+    if let Err(e) = verifier
+        .verify(authorization.as_deref(), &activity.service_url)
+        .await
+    {
+        warn!("Activity is not from Bot Framework: {e:#}");
+        return to_response(StatusCode::UNAUTHORIZED, &Value::Null);
+    }
+    // NOTE: Other activities like the bot being added to a team are acknowledged and ignored
+    let Some(action) = to_action(&activity) else {
+        debug!("Activity is ignored: {}", activity.kind);
+        return to_response(StatusCode::OK, &Value::Null);
+    };
+    if action.data["approval_id"].as_str() != Some(approval_id) {
+        return to_response(
+            StatusCode::OK,
+            &invoke_response("This approval is not waiting for a decision anymore"),
+        );
+    }
+
+    let interaction = match to_interaction(&activity, action) {
+        Ok(interaction) => interaction,
+        Err(e) => {
+            warn!("{e:#}");
+            return to_response(StatusCode::OK, &invoke_response(&e.to_string()));
+        }
+    };
+    if sender.send(interaction).is_err() {
+        return to_response(
+            StatusCode::OK,
+            &invoke_response("This approval is not waiting for a decision anymore"),
+        );
+    }
+
+    // NOTE: Every copy of the card is updated separately once the click is checked
+    to_response(StatusCode::OK, &invoke_response("Received"))
+}
+
+async fn read_activity(req: Request<Incoming>) -> Result<Activity> {
+    let body = req.into_body().collect().await?.to_bytes();
+    serde_json::from_slice(&body).with_context(|| "Invalid activity")
+}
+
+// None for activities other than a click on the card
+fn to_action(activity: &Activity) -> Option<&CardAction> {
+    if activity.kind != "invoke" || activity.name.as_deref() != Some(ACTION_INVOKE) {
+        return None;
+    }
+    activity.value.as_ref().map(|value| &value.action)
+}
+
+fn to_interaction(activity: &Activity, action: &CardAction) -> Result<Interaction> {
+    let decision = match action.verb.as_str() {
+        APPROVE_VERB => Decision::Approved,
+        REJECT_VERB => Decision::Rejected,
+        verb => bail!("Unknown verb: {verb}"),
+    };
+    let Some(user_id) = activity.from.aad_object_id.clone() else {
+        bail!("Click has no Microsoft Entra user");
+    };
+
+    Ok(Interaction {
+        mention: activity
+            .from
+            .name
+            .clone()
+            .unwrap_or_else(|| user_id.clone()),
+        user_id,
+        // NOTE: Clicks in a channel come from the thread of the card, like `19:...@thread.tacv2;messageid=...`
+        channel_id: activity
+            .conversation
+            .id
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .into(),
+        decision,
+    })
+}
+
+// Shown to the user who clicked
+fn invoke_response(message: &str) -> Value {
+    json!({
+        "statusCode": 200,
+        "type": "application/vnd.microsoft.activity.message",
+        "value": message,
+    })
+}
+
+fn to_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let body = if body.is_null() {
+        Bytes::new()
+    } else {
+        Bytes::from(body.to_string())
+    };
+    let mut res = Response::new(Full::new(body));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(verb: &str, conversation_id: &str) -> Activity {
+        serde_json::from_value(json!({
+            "type": "invoke",
+            "name": ACTION_INVOKE,
+            "serviceUrl": "https://smba.trafficmanager.net/teams/",
+            "from": {
+                "id": "29:1a2b3c",
+                "name": "Alice",
+                "aadObjectId": "6b2e9a40-0000-0000-0000-000000000001",
+            },
+            "conversation": { "id": conversation_id },
+            "value": { "action": { "type": "Action.Execute", "verb": verb, "data": {} } },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_convert_click_to_interaction() {
+        let activity = activity(
+            APPROVE_VERB,
+            "19:4a1f2b3c@thread.tacv2;messageid=1700000000000",
+        );
+
+        let actual = to_interaction(&activity, to_action(&activity).unwrap()).unwrap();
+
+        assert_eq!(
+            actual,
+            Interaction {
+                user_id: "6b2e9a40-0000-0000-0000-000000000001".into(),
+                mention: "Alice".into(),
+                channel_id: "19:4a1f2b3c@thread.tacv2".into(),
+                decision: Decision::Approved,
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_convert_unknown_verb() {
+        let activity = activity("deploy", "19:4a1f2b3c@thread.tacv2");
+
+        assert!(to_interaction(&activity, to_action(&activity).unwrap()).is_err());
+    }
+}
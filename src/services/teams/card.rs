@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::services::ci::CiInfo;
+use crate::services::github::github_inputs::GitHubInputs;
+use crate::services::template::TemplateContext;
+
+pub(super) const APPROVE_VERB: &str = "approve";
+pub(super) const REJECT_VERB: &str = "reject";
+
+// Adaptive Card of the approval
+// https://learn.microsoft.com/en-us/microsoftteams/platform/task-modules-and-cards/cards/cards-reference#adaptive-card
+pub(super) fn build_card(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+) -> Result<Value> {
+    let mut body = vec![];
+    if let Some(title) = &github_inputs.title {
+        let title = context
+            .render(title)
+            .with_context(|| "Failed to render title")?;
+        body.push(json!({
+            "type": "TextBlock",
+            "text": title,
+            "size": "Large",
+            "weight": "Bolder",
+            "wrap": true,
+        }));
+    }
+    // NOTE: TextBlock shows a subset of Markdown, which is enough for most descriptions
+    if let Some(description) = &github_inputs.description {
+        let description = context
+            .render(description)
+            .with_context(|| "Failed to render description")?;
+        body.push(json!({ "type": "TextBlock", "text": description, "wrap": true }));
+    }
+    if let Some(path) = &github_inputs.description_file {
+        let description = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read description file: {path}"))?;
+        body.push(json!({ "type": "TextBlock", "text": description, "wrap": true }));
+    }
+    body.push(json!({
+        "type": "FactSet",
+        "facts": build_facts(github_inputs, ci_info, context)?,
+    }));
+
+    Ok(json!({
+        "type": "AdaptiveCard",
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        // Action.Execute needs 1.4
+        "version": "1.4",
+        "body": body,
+        "msteams": { "width": "Full" },
+    }))
+}
+
+// Teams sends the click to the messaging endpoint of the bot as an `adaptiveCard/action` invoke
+// NOTE: The ID tells the clicks on this approval from those on older ones of the same bot
+pub(super) fn build_actions(approval_id: &str) -> Value {
+    let action = |verb: &str, title: &str, style: &str| {
+        json!({
+            "type": "Action.Execute",
+            "title": title,
+            "verb": verb,
+            "style": style,
+            "data": { "approval_id": approval_id },
+        })
+    };
+
+    json!([
+        action(APPROVE_VERB, "✅Approve", "positive"),
+        action(REJECT_VERB, "❌Reject", "destructive"),
+    ])
+}
+
+// Removes the buttons so that no one can decide anymore and shows the result instead
+pub(super) fn replace_actions(card: &Value, text: &str) -> Value {
+    let mut card = card.clone();
+    if let Some(card) = card.as_object_mut() {
+        card.remove("actions");
+    }
+    if let Some(body) = card["body"].as_array_mut() {
+        body.push(json!({
+            "type": "TextBlock",
+            "text": text,
+            "weight": "Bolder",
+            "wrap": true,
+        }));
+    }
+
+    card
+}
+
+// Message which carries the card
+pub(super) fn to_activity(card: &Value) -> Value {
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": card,
+        }],
+    })
+}
+
+fn build_facts(
+    github_inputs: &GitHubInputs,
+    ci_info: &dyn CiInfo,
+    context: &TemplateContext,
+) -> Result<Vec<Value>> {
+    let fact = |title: &str, value: String| json!({ "title": title, "value": value });
+    let labels = ci_info.labels();
+    let mut facts = vec![];
+    if !ci_info.actor().is_empty() {
+        facts.push(fact("👤Actor", ci_info.actor().into()));
+    }
+    if let Some(url) = ci_info.repository_url() {
+        facts.push(fact(
+            "📦Repository",
+            format!("[{}]({url})", ci_info.repository()),
+        ));
+    }
+    if let Some(url) = ci_info.run_url() {
+        facts.push(fact(
+            &format!("🚀{}", labels.run),
+            format!("[#{}]({url})", ci_info.run_number()),
+        ));
+    }
+    let workflow = [ci_info.workflow(), ci_info.job()]
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" / ");
+    if !workflow.is_empty() {
+        facts.push(fact(&format!("🔄{}", labels.workflow), workflow));
+    }
+
+    for input in &github_inputs.fields {
+        let value = context
+            .render(&input.value)
+            .with_context(|| format!("Failed to render field: {}", input.name))?;
+        facts.push(fact(&input.name, value));
+    }
+
+    Ok(facts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+
+    #[test]
+    fn should_build_card() {
+        let github_inputs = GitHubInputs::for_test(&[
+            ("platform", "teams"),
+            ("bot-token", "teams-client-secret"),
+            ("channel-id", "19:4a1f2b3c@thread.tacv2"),
+            ("teams-app-id", "00000000-0000-0000-0000-000000000001"),
+            ("teams-tenant-id", "00000000-0000-0000-0000-000000000002"),
+            ("mode", "notify"),
+            ("title", "Deploy run #${{ github.run_number }}"),
+            ("fields", "Target: ${{ github.repository }}"),
+        ]);
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = build_card(&github_inputs, &github_info, &context).unwrap();
+
+        assert_eq!(actual["body"][0]["text"], "Deploy run #7");
+        let facts = actual["body"][1]["facts"].as_array().unwrap();
+        assert_eq!(
+            facts.last().unwrap(),
+            &json!({ "title": "Target", "value": "octocat/Hello-World" })
+        );
+    }
+
+    #[test]
+    fn should_replace_actions_with_outcome() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "body": [{ "type": "TextBlock", "text": "Deploy" }],
+            "actions": build_actions("approval"),
+        });
+
+        let actual = replace_actions(&card, "Approved by Alice");
+
+        assert_eq!(
+            actual,
+            json!({
+                "type": "AdaptiveCard",
+                "body": [
+                    { "type": "TextBlock", "text": "Deploy" },
+                    { "type": "TextBlock", "text": "Approved by Alice", "weight": "Bolder", "wrap": true },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use tracing::{info, warn};
+
+use crate::services::chat::callback::Callback;
+use crate::services::chat::{self, Interaction, Outcome, Transport};
+use crate::services::ci::CiInfo;
+use crate::services::github::github_inputs::{GitHubInputs, Mode};
+use crate::services::network;
+use crate::services::template::TemplateContext;
+use crate::services::termination::Termination;
+use api::{Endpoints, TeamsApi};
+use auth::Verifier;
+use callback::ActivityRoute;
+
+mod api;
+mod auth;
+mod callback;
+mod card;
+#[cfg(test)]
+mod stand_in;
+
+pub async fn handle_teams_approval(
+    ci_info: &dyn CiInfo,
+    github_inputs: &GitHubInputs,
+    termination: Termination,
+) -> Result<()> {
+    approve(ci_info, github_inputs, Endpoints::default(), termination).await
+}
+
+async fn approve(
+    ci_info: &dyn CiInfo,
+    github_inputs: &GitHubInputs,
+    endpoints: Endpoints,
+    termination: Termination,
+) -> Result<()> {
+    let Some(teams) = &github_inputs.teams else {
+        bail!("Input 'teams-app-id' is required");
+    };
+    chat::warn_unsupported_inputs(github_inputs, "Teams");
+    if !github_inputs.mention_to_users.is_empty() || !github_inputs.mention_to_groups.is_empty() {
+        warn!(
+            "Inputs 'mention-to-users' and 'mention-to-groups' are not supported on Teams and are ignored"
+        );
+    }
+    chat::check_destroy_policy(github_inputs, "Teams")?;
+
+    let context = TemplateContext::new(ci_info)?;
+    let mut card = card::build_card(github_inputs, ci_info, &context)
+        .with_context(|| "Failed to build card")?;
+
+    let client = network::http_client(&github_inputs.network)?;
+    let callback = match (github_inputs.mode, &teams.listen_address) {
+        (Mode::Approval, Some(listen_address)) => {
+            let approval_id = chat::generate_secret()?;
+            card["actions"] = card::build_actions(&approval_id);
+            let verifier = Verifier::new(client.clone(), &endpoints.openid_url, &teams.app_id);
+            let route = ActivityRoute {
+                approval_id,
+                verifier,
+            };
+            let callback = Callback::listen(listen_address, route).await?;
+            info!("Listening for clicks on {}", callback.addr());
+            Some(callback)
+        }
+        (Mode::Approval, None) => bail!("Input 'teams-listen-address' is required"),
+        (Mode::Notify, _) => None,
+    };
+
+    let transport = TeamsTransport {
+        api: TeamsApi::new(client, endpoints, teams, &github_inputs.bot_token),
+        card,
+        activities: vec![],
+        callback,
+    };
+    chat::run_approval(transport, github_inputs, termination, |transport| {
+        let Some(activity) = transport.activities.first() else {
+            bail!("Approval was not posted to anywhere");
+        };
+        Ok(vec![
+            ("channel-id", activity.conversation_id.clone()),
+            // NOTE: Teams identifies a message by its activity ID instead of a timestamp
+            ("ts", activity.id.clone()),
+        ])
+    })
+    .await
+}
+
+// A copy of the card
+struct PostedActivity {
+    conversation_id: String,
+    id: String,
+}
+
+// Posts the approval as an Adaptive Card and receives the clicks on the messaging endpoint
+struct TeamsTransport {
+    api: TeamsApi,
+    card: Value,
+    // Every copy is updated once someone decides
+    activities: Vec<PostedActivity>,
+    callback: Option<Callback>,
+}
+
+impl Transport for TeamsTransport {
+    async fn post(&mut self, channel_id: &str) -> Result<()> {
+        let id = self
+            .api
+            .send_activity(channel_id, &card::to_activity(&self.card))
+            .await
+            .with_context(|| format!("Failed to post approval. conversation_id: {channel_id}"))?;
+        self.activities.push(PostedActivity {
+            conversation_id: channel_id.into(),
+            id,
+        });
+
+        Ok(())
+    }
+
+    async fn update(&self, outcome: &Outcome) -> Result<()> {
+        let card = card::replace_actions(&self.card, &outcome.text());
+        chat::update_each(self.activities.iter().map(|activity| async {
+            let mut body = card::to_activity(&card);
+            body["id"] = activity.id.clone().into();
+            self.api
+                .update_activity(&activity.conversation_id, &activity.id, &body)
+                .await
+                .with_context(|| format!("Failed to update approval. activity_id: {}", activity.id))
+        }))
+        .await
+    }
+
+    // Replies to the copy of the approval in the conversation where the button was clicked
+    async fn notice(&self, interaction: &Interaction, text: &str) -> Result<()> {
+        let Some(activity) = self
+            .activities
+            .iter()
+            .find(|activity| activity.conversation_id == interaction.channel_id)
+        else {
+            bail!(
+                "Approval is not in conversation: {}",
+                interaction.channel_id
+            );
+        };
+        self.api
+            .reply_to_activity(
+                &activity.conversation_id,
+                &activity.id,
+                &json!({ "type": "message", "text": text }),
+            )
+            .await
+    }
+
+    async fn group_members(&self, group_id: &str) -> Result<Vec<String>> {
+        self.api.group_members(group_id).await
+    }
+
+    async fn next_interaction(&mut self) -> Result<Interaction> {
+        let Some(callback) = &mut self.callback else {
+            bail!("Not listening for clicks");
+        };
+        callback.next().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::services::config::Config;
+    use crate::services::github::github_info::GitHubInfo;
+    use crate::services::github::github_inputs::read_github_inputs;
+    use stand_in::StandIn;
+
+    const APP_ID: &str = "00000000-0000-0000-0000-000000000001";
+    pub(super) const CONVERSATION_ID: &str = "19:4a1f2b3c@thread.tacv2";
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[rstest]
+    #[case::approve("approve", true)]
+    #[case::reject("reject", false)]
+    #[tokio::test]
+    async fn should_decide_through_stand_in(#[case] verb: &str, #[case] expected: bool) {
+        let stand_in = StandIn::start(vec![("G1".into(), vec!["aad-bob".into()])]).await;
+        let listen_address = format!("127.0.0.1:{}", free_port());
+        let callback_url = format!("http://{listen_address}/api/messages");
+        let github_inputs = read_github_inputs(&Config::from_values(&[
+            ("platform", "teams"),
+            ("bot-token", "teams-client-secret"),
+            ("channel-id", CONVERSATION_ID),
+            ("teams-app-id", APP_ID),
+            ("teams-tenant-id", "00000000-0000-0000-0000-000000000002"),
+            ("teams-service-url", &stand_in.url()),
+            ("teams-listen-address", &listen_address),
+            ("authorized-groups", "G1"),
+            ("title", "Deploy"),
+        ]))
+        .unwrap();
+        let github_info = GitHubInfo::for_test();
+        let termination = Termination::install(Some(Duration::from_secs(10))).unwrap();
+
+        let click = async {
+            let approval_id = stand_in.wait_for_approval_id().await;
+            let res = stand_in
+                .click(&callback_url, None, "aad-eve", "Eve", verb, &approval_id)
+                .await;
+            assert_eq!(res, 401);
+            let res = stand_in
+                .click(
+                    &callback_url,
+                    Some(APP_ID),
+                    "aad-eve",
+                    "Eve",
+                    verb,
+                    &approval_id,
+                )
+                .await;
+            assert_eq!(res, 200);
+            let res = stand_in
+                .click(
+                    &callback_url,
+                    Some(APP_ID),
+                    "aad-bob",
+                    "Bob",
+                    verb,
+                    &approval_id,
+                )
+                .await;
+            assert_eq!(res, 200);
+        };
+        let (actual, _) = tokio::join!(
+            approve(
+                &github_info,
+                &github_inputs,
+                stand_in.endpoints(),
+                termination
+            ),
+            click
+        );
+
+        assert_eq!(actual.is_ok(), expected);
+        let requests = stand_in.requests();
+        let reply = requests
+            .iter()
+            .find(|(method, path, _)| method == "POST" && path.ends_with("/activities/activity-1"))
+            .unwrap();
+        assert_eq!(
+            reply.2["text"],
+            format!("You are not authorized to {verb} this action: aad-eve")
+        );
+        let (_, _, update) = requests
+            .iter()
+            .find(|(method, _, _)| method == "PUT")
+            .unwrap();
+        let card = &update["attachments"][0]["content"];
+        assert_eq!(card.get("actions"), None);
+        let decided = if expected { "Approved" } else { "Rejected" };
+        assert_eq!(
+            card["body"].as_array().unwrap().last().unwrap()["text"],
+            format!("{decided} by Bob")
+        );
+        stand_in.shutdown();
+    }
+}
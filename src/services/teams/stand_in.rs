@@ -0,0 +1,179 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http::{Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use super::api::Endpoints;
+use super::auth::{self, tests as auth_tests};
+
+struct State {
+    addr: SocketAddr,
+    // Group ID and its members
+    groups: Vec<(String, Vec<String>)>,
+    // Method, path and body of the calls to the Bot Connector
+    requests: Mutex<Vec<(String, String, Value)>>,
+    activities: AtomicU64,
+}
+
+// Login, Graph and the Bot Connector of Microsoft, and Bot Framework sending the clicks signed with a test key
+pub(super) struct StandIn {
+    state: Arc<State>,
+    task: JoinHandle<()>,
+}
+
+impl StandIn {
+    pub(super) async fn start(groups: Vec<(String, Vec<String>)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let state = Arc::new(State {
+            addr: listener.local_addr().unwrap(),
+            groups,
+            requests: Mutex::new(vec![]),
+            activities: AtomicU64::new(0),
+        });
+
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |req| {
+                            let state = state.clone();
+                            async move { Ok::<_, Infallible>(route(req, &state).await) }
+                        });
+                        let _ = http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            }
+        });
+
+        Self { state, task }
+    }
+
+    pub(super) fn url(&self) -> String {
+        format!("http://{}", self.state.addr)
+    }
+
+    pub(super) fn endpoints(&self) -> Endpoints {
+        Endpoints {
+            login_url: self.url(),
+            graph_url: self.url(),
+            openid_url: format!("{}/openid", self.url()),
+        }
+    }
+
+    pub(super) fn requests(&self) -> Vec<(String, String, Value)> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    // From the buttons of the first card which is posted
+    pub(super) async fn wait_for_approval_id(&self) -> String {
+        loop {
+            let approval_id = self.requests().iter().find_map(|(_, _, body)| {
+                body["attachments"][0]["content"]["actions"][0]["data"]["approval_id"]
+                    .as_str()
+                    .map(String::from)
+            });
+            if let Some(approval_id) = approval_id {
+                return approval_id;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    // Sends the invoke of Action.Execute, signed for `audience` unless it is None
+    pub(super) async fn click(
+        &self,
+        callback_url: &str,
+        audience: Option<&str>,
+        aad_object_id: &str,
+        name: &str,
+        verb: &str,
+        approval_id: &str,
+    ) -> u16 {
+        let activity = json!({
+            "type": "invoke",
+            "name": "adaptiveCard/action",
+            "serviceUrl": self.url(),
+            "from": { "id": format!("29:{aad_object_id}"), "name": name, "aadObjectId": aad_object_id },
+            "conversation": { "id": format!("{};messageid=1700000000000", super::tests::CONVERSATION_ID) },
+            "value": {
+                "action": { "type": "Action.Execute", "verb": verb, "data": { "approval_id": approval_id } },
+            },
+        });
+        let mut req = reqwest::Client::new().post(callback_url).json(&activity);
+        if let Some(audience) = audience {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            req = req.bearer_auth(auth_tests::sign(&json!({
+                "iss": auth::ISSUER,
+                "aud": audience,
+                "exp": now + 300,
+                "nbf": now,
+                "serviceUrl": self.url(),
+            })));
+        }
+
+        req.send().await.unwrap().status().as_u16()
+    }
+
+    pub(super) fn shutdown(self) {
+        self.task.abort();
+    }
+}
+
+async fn route(req: Request<Incoming>, state: &State) -> Response<Full<Bytes>> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let body = req.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+
+    let res = match (&method, path.as_str()) {
+        (&Method::POST, path) if path.ends_with("/oauth2/v2.0/token") => {
+            json!({ "token_type": "Bearer", "access_token": "stand-in", "expires_in": 3600 })
+        }
+        (&Method::GET, "/openid") => json!({ "jwks_uri": format!("http://{}/keys", state.addr) }),
+        (&Method::GET, "/keys") => auth_tests::test_jwks(),
+        (&Method::GET, path) if path.starts_with("/v1.0/groups/") => {
+            let group_id = path.split('/').nth(3).unwrap_or_default();
+            let members = state
+                .groups
+                .iter()
+                .find(|(id, _)| id == group_id)
+                .map(|(_, users)| users.clone())
+                .unwrap_or_default();
+            json!({ "value": members.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>() })
+        }
+        (_, path) if path.starts_with("/v3/conversations/") => {
+            state
+                .requests
+                .lock()
+                .unwrap()
+                .push((method.to_string(), path.into(), body));
+            let id = state.activities.fetch_add(1, Ordering::Relaxed) + 1;
+            json!({ "id": format!("activity-{id}") })
+        }
+        _ => {
+            let mut res = Response::new(Full::new(Bytes::new()));
+            *res.status_mut() = StatusCode::NOT_FOUND;
+            return res;
+        }
+    };
+
+    Response::new(Full::new(Bytes::from(res.to_string())))
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use crate::services::ci::CiInfo;
+
+const PLACEHOLDER_START: &str = "${{";
+const PLACEHOLDER_END: &str = "}}";
+
+// Environment variables which may have secrets, like the inputs of the action and the runtime token of GitHub Actions
+const HIDDEN_ENV_PREFIXES: &[&str] = &["INPUT_", "ACTIONS_", "SLACK_APPROVAL_"];
+const HIDDEN_ENV_KEYWORDS: &[&str] = &["TOKEN", "SECRET", "PASSWORD"];
+
+// Resolves `${{ name }}` placeholders like GitHub Actions expressions
+// Supported names:
+// - `github.actor`, `github.repository`, `github.repository_url`, `github.run_id`,
+//   `github.run_number`, `github.run_attempt`, `github.run_url`, `github.server_url`,
+//   `github.workflow`, `github.job`, `github.sha`, `github.ref_name`, `github.event_name`,
+//   `runner.os`
+// - `env.<NAME>` for environment variables, except the ones which may have secrets
+// - `github.event.<path>` for values in the event payload (e.g. `github.event.inputs.version`),
+//   which are escaped with `with_escape` as anyone who opens a pull request can write them
+// NOTE: In other CIs, `github.*` and `runner.os` are the equivalent values, and the event is empty
+// - Names added with `with_value`
+pub struct TemplateContext<'a> {
+    ci_info: &'a dyn CiInfo,
+    event: Option<Value>,
+    values: HashMap<String, String>,
+    escape: fn(&str) -> String,
+}
+
+impl<'a> TemplateContext<'a> {
+    pub fn new(ci_info: &'a dyn CiInfo) -> Result<Self> {
+        Ok(Self {
+            ci_info,
+            event: match ci_info.github() {
+                Some(github_info) => github_info.read_event()?,
+                None => None,
+            },
+            values: HashMap::new(),
+            escape: str::to_string,
+        })
+    }
+
+    pub fn event(&self) -> Option<&Value> {
+        self.event.as_ref()
+    }
+
+    pub fn with_value(mut self, name: &str, value: String) -> Self {
+        self.values.insert(name.into(), value);
+        self
+    }
+
+    // Escapes the values in the event payload for the markup of the message
+    pub fn with_escape(mut self, escape: fn(&str) -> String) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    pub fn render(&self, template: &str) -> Result<String> {
+        let mut rendered = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find(PLACEHOLDER_START) {
+            rendered.push_str(&rest[..start]);
+            let after_start = &rest[start + PLACEHOLDER_START.len()..];
+            let Some(end) = after_start.find(PLACEHOLDER_END) else {
+                bail!("Placeholder is not closed with '{PLACEHOLDER_END}': {template}");
+            };
+            rendered.push_str(&self.resolve(after_start[..end].trim())?);
+            rest = &after_start[end + PLACEHOLDER_END.len()..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+
+    fn resolve(&self, name: &str) -> Result<String> {
+        if let Some(v) = self.values.get(name) {
+            return Ok(v.clone());
+        }
+        if let Some(env_name) = name.strip_prefix("env.") {
+            if is_hidden_env(env_name) {
+                bail!("Environment variable is not allowed in placeholder: {env_name}");
+            }
+            return Ok(std::env::var(env_name).unwrap_or_default());
+        }
+        if let Some(path) = name.strip_prefix("github.event.") {
+            return Ok(self
+                .event
+                .as_ref()
+                .and_then(|event| lookup_value(event, path))
+                .map(|value| (self.escape)(&value_to_string(value)))
+                .unwrap_or_default());
+        }
+
+        let ci_info = self.ci_info;
+        let v = match name {
+            "github.actor" => ci_info.actor().into(),
+            "github.repository" => ci_info.repository().into(),
+            "github.repository_url" => ci_info.repository_url().unwrap_or_default(),
+            "github.run_id" => ci_info.run_id().into(),
+            "github.run_number" => ci_info.run_number().into(),
+            "github.run_url" => ci_info.run_url().unwrap_or_default(),
+            "github.server_url" => ci_info
+                .github()
+                .map(|github_info| github_info.github_server_url.clone())
+                .unwrap_or_default(),
+            "github.workflow" => ci_info.workflow().into(),
+            "github.sha" => ci_info.sha().into(),
+            "github.ref_name" => ci_info.ref_name().into(),
+            "github.event_name" => ci_info
+                .github()
+                .map(|github_info| github_info.github_event_name.clone())
+                .unwrap_or_default(),
+            "github.job" => ci_info.job().into(),
+            "github.run_attempt" => ci_info.run_attempt().unwrap_or("1").into(),
+            "runner.os" => ci_info.runner().into(),
+            _ => bail!("Unknown placeholder: {name}"),
+        };
+
+        Ok(v)
+    }
+}
+
+fn is_hidden_env(name: &str) -> bool {
+    let name = name.to_uppercase();
+    HIDDEN_ENV_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        || HIDDEN_ENV_KEYWORDS
+            .iter()
+            .any(|keyword| name.contains(keyword))
+}
+
+// Path is separated by dots, and array elements are accessed by index (e.g. `commits.0.id`)
+fn lookup_value<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Object(map) => map.get(key),
+        Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(v) => v.clone(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::github::github_info::GitHubInfo;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("no placeholder", Ok("no placeholder".into()))]
+    #[case("Deploy by ${{ github.actor }}", Ok("Deploy by octocat".into()))]
+    #[case("${{github.repository}}#${{ github.run_number }}", Ok("octocat/Hello-World#7".into()))]
+    #[case(
+        "${{ github.run_url }}",
+        Ok("https://github.com/octocat/Hello-World/actions/runs/42".into())
+    )]
+    #[case("v${{ github.event.inputs.version }} to ${{ github.event.inputs.env }}", Ok("v1.42 to prod-eu".into()))]
+    #[case("${{ github.event.commits.1.id }}", Ok("def".into()))]
+    #[case("${{ github.event.forced }}", Ok("false".into()))]
+    #[case("[${{ github.event.missing }}]", Ok("[]".into()))]
+    #[case("${{ github.ref_name }}@${{ github.sha }}", Ok("main@ffac537e6cbbf934b08745a378932722df287a53".into()))]
+    #[case("[${{ env.TEMPLATE_TEST_UNSET }}]", Ok("[]".into()))]
+    #[case("${{ approval.mentions }} deploy", Ok("<@U000001> deploy".into()))]
+    #[case("${{ github.token }}", Err("Unknown placeholder: github.token".into()))]
+    #[case("${{ env.GITHUB_TOKEN }}", Err("Environment variable is not allowed in placeholder: GITHUB_TOKEN".into()))]
+    #[case("${{ env.INPUT_BOT-TOKEN }}", Err("Environment variable is not allowed in placeholder: INPUT_BOT-TOKEN".into()))]
+    #[case("${{ env.ACTIONS_RUNTIME_URL }}", Err("Environment variable is not allowed in placeholder: ACTIONS_RUNTIME_URL".into()))]
+    #[case("${{ env.aws_secret_access_key }}", Err("Environment variable is not allowed in placeholder: aws_secret_access_key".into()))]
+    #[case("${{ github.actor", Err("Placeholder is not closed with '}}': ${{ github.actor".into()))]
+    fn test_render(#[case] template: &str, #[case] expected: Result<String, String>) {
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext {
+            ci_info: &github_info,
+            event: Some(json!({
+                "inputs": { "version": "1.42", "env": "prod-eu" },
+                "commits": [{ "id": "abc" }, { "id": "def" }],
+                "forced": false,
+            })),
+            values: HashMap::new(),
+            escape: str::to_string,
+        }
+        .with_value("approval.mentions", "<@U000001>".into());
+
+        let actual = context.render(template).map_err(|e| e.to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_render_env() {
+        unsafe { std::env::set_var("TEMPLATE_TEST_SERVICE", "payments-api") };
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext::new(&github_info).unwrap();
+
+        let actual = context
+            .render("Deploy ${{ env.TEMPLATE_TEST_SERVICE }}")
+            .unwrap();
+        assert_eq!(actual, "Deploy payments-api");
+    }
+
+    #[test]
+    fn should_escape_event_values() {
+        let github_info = GitHubInfo::for_test();
+        let context = TemplateContext {
+            ci_info: &github_info,
+            event: Some(json!({ "pull_request": { "title": "Fix <!channel> & more" } })),
+            values: HashMap::new(),
+            escape: str::to_string,
+        }
+        .with_escape(|text| text.replace('<', "&lt;"));
+
+        let actual = context
+            .render("${{ github.event.pull_request.title }} by ${{ github.actor }}")
+            .unwrap();
+        assert_eq!(actual, "Fix &lt;!channel> & more by octocat");
+    }
+}
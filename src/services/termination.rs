@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::signal::unix::{Signal, SignalKind, signal};
+use tokio::time::Instant;
+use tracing::info;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TerminationReason {
+    // SIGINT or SIGTERM is received (e.g. the workflow run is cancelled)
+    Cancelled,
+    // No one decided within `approval-timeout-minutes`
+    TimedOut,
+}
+
+pub struct Termination {
+    sigint: Signal,
+    sigterm: Signal,
+    deadline: Option<Instant>,
+}
+
+impl Termination {
+    // Signals received after this call are kept until `wait` is called
+    pub fn install(timeout: Option<Duration>) -> Result<Self> {
+        Ok(Self {
+            sigint: signal(SignalKind::interrupt())
+                .with_context(|| "Failed to install SIGINT handler")?,
+            sigterm: signal(SignalKind::terminate())
+                .with_context(|| "Failed to install SIGTERM handler")?,
+            deadline: timeout
+                .map(|timeout| {
+                    Instant::now()
+                        .checked_add(timeout)
+                        .with_context(|| format!("Timeout is too long: {timeout:?}"))
+                })
+                .transpose()?,
+        })
+    }
+
+    pub async fn wait(&mut self) -> TerminationReason {
+        let timeout = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        let reason = tokio::select! {
+            _ = self.sigint.recv() => TerminationReason::Cancelled,
+            _ = self.sigterm.recv() => TerminationReason::Cancelled,
+            _ = timeout => TerminationReason::TimedOut,
+        };
+        info!("Terminating: {:?}", reason);
+
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_time_out_after_deadline() {
+        let mut termination = Termination::install(Some(Duration::from_millis(10))).unwrap();
+        assert_eq!(termination.wait().await, TerminationReason::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn should_reject_timeout_beyond_clock() {
+        assert!(Termination::install(Some(Duration::MAX)).is_err());
+    }
+}
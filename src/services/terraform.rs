@@ -0,0 +1,123 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+// Output of `terraform show -json plan.out`
+// https://developer.hashicorp.com/terraform/internals/json-format#plan-representation
+#[derive(Deserialize)]
+struct PlanJson {
+    #[serde(default)]
+    resource_changes: Vec<ResourceChangeJson>,
+}
+
+#[derive(Deserialize)]
+struct ResourceChangeJson {
+    address: String,
+    change: ChangeJson,
+}
+
+#[derive(Deserialize)]
+struct ChangeJson {
+    actions: Vec<String>,
+}
+
+// Counts like the last line of `terraform plan`, where a replacement is both added and destroyed
+#[derive(Debug, PartialEq, Default)]
+pub struct TerraformPlan {
+    pub add: usize,
+    pub change: usize,
+    pub destroy: usize,
+    // Addresses of the resources deleted without being recreated
+    pub destroyed: Vec<String>,
+    pub replaced: Vec<String>,
+}
+
+impl TerraformPlan {
+    pub fn has_destroys(&self) -> bool {
+        self.destroy > 0
+    }
+}
+
+pub fn read_plan(path: &str) -> Result<TerraformPlan> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open Terraform plan: {path}"))?;
+    let plan: PlanJson = serde_json::from_reader(BufReader::new(file)).with_context(|| {
+        format!("Failed to parse Terraform plan. Is it the output of `terraform show -json`? path: {path}")
+    })?;
+
+    Ok(summarize(plan))
+}
+
+fn summarize(plan: PlanJson) -> TerraformPlan {
+    let mut summary = TerraformPlan::default();
+    for resource in plan.resource_changes {
+        let actions: Vec<&str> = resource.change.actions.iter().map(|a| a.as_str()).collect();
+        match actions.as_slice() {
+            ["create"] => summary.add += 1,
+            ["update"] => summary.change += 1,
+            ["delete"] => {
+                summary.destroy += 1;
+                summary.destroyed.push(resource.address);
+            }
+            // NOTE: Replacement is either create-before-destroy or destroy-before-create
+            ["delete", "create"] | ["create", "delete"] => {
+                summary.add += 1;
+                summary.destroy += 1;
+                summary.replaced.push(resource.address);
+            }
+            // `no-op` and `read` do not change the infrastructure
+            _ => {}
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_summarize_plan() {
+        let plan: PlanJson = serde_json::from_str(
+            r#"{
+                "format_version": "1.2",
+                "resource_changes": [
+                    {"address": "aws_s3_bucket.assets", "change": {"actions": ["create"]}},
+                    {"address": "aws_iam_role.app", "change": {"actions": ["update"]}},
+                    {"address": "aws_instance.web[0]", "change": {"actions": ["delete", "create"]}},
+                    {"address": "aws_db_instance.main", "change": {"actions": ["delete"]}},
+                    {"address": "data.aws_ami.ubuntu", "change": {"actions": ["read"]}},
+                    {"address": "aws_vpc.main", "change": {"actions": ["no-op"]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let actual = summarize(plan);
+
+        assert_eq!(
+            actual,
+            TerraformPlan {
+                add: 2,
+                change: 1,
+                destroy: 2,
+                destroyed: vec!["aws_db_instance.main".into()],
+                replaced: vec!["aws_instance.web[0]".into()],
+            }
+        );
+        assert!(actual.has_destroys());
+    }
+
+    #[test]
+    fn should_summarize_plan_without_changes() {
+        let plan: PlanJson = serde_json::from_str(r#"{"format_version": "1.2"}"#).unwrap();
+
+        let actual = summarize(plan);
+
+        assert_eq!(actual, TerraformPlan::default());
+        assert!(!actual.has_destroys());
+    }
+}